@@ -0,0 +1,781 @@
+use rodio::Source;
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::bands::{band_energies, default_bands, Band};
+use crate::binning::{SpectrumBinner, SpectrumLayout};
+use crate::chroma::ChromaTracker;
+use crate::decoder::open_track;
+use crate::envelope::{FeatureShaper, FeatureShaping};
+use crate::error::VisualizerError;
+use crate::features::{AudioFeatures, FeaturePublisher, BEAT_RETENTION};
+use crate::onset::{BeatEvent, OnsetDetector};
+use crate::playback::{PlaybackClock, SampleRing, TeeSource};
+use crate::stereo::StereoAnalyzer;
+use crate::tempo::TempoTracker;
+use crate::window::WindowFunction;
+
+pub const MIN_DB: f32 = -60.0;
+pub const MAX_DB: f32 = 0.0;
+// Analiz halkasının boyu (çerçeve); en büyük FFT ve gecikme payı için yeterli
+pub const RING_CAPACITY: usize = 1 << 17;
+// Çalma saati bir sonraki hop'a ulaşmadıysa analiz iş parçacığının bekleme süresi
+pub const ANALYSIS_POLL: Duration = Duration::from_millis(4);
+// Çevrimdışı analizde halka dolana kadar kaç örnekte bir kontrol edileceği
+const DECODE_BATCH: usize = 4096;
+
+#[derive(Clone, Debug)]
+pub struct AnalyzerConfig {
+    pub fft_size: usize,
+    pub hop_size: usize,
+    pub window: WindowFunction,
+    pub latency_ms: i32,
+    pub bands: Vec<Band>,
+    // Bant başına atak/bırakma ve otomatik kazanç; `bands` ile aynı sırada
+    pub shaping: Vec<FeatureShaping>,
+    pub spectrum: SpectrumLayout,
+    pub onset_sensitivity: f32,
+}
+
+impl Default for AnalyzerConfig {
+    fn default() -> Self {
+        Self {
+            fft_size: 2048,
+            hop_size: 1024,
+            window: WindowFunction::Hann,
+            latency_ms: 0,
+            bands: default_bands(),
+            shaping: vec![FeatureShaping::default(); default_bands().len()],
+            spectrum: SpectrumLayout::default(),
+            onset_sensitivity: 1.5,
+        }
+    }
+}
+
+// FFT planı, pencere ve ara tamponlar kareler arasında yeniden kullanılır
+pub struct SpectrumAnalyzer {
+    fft: Arc<dyn Fft<f32>>,
+    window: Vec<f32>,
+    amplitude_scale: f32,
+    buffer: Vec<Complex<f32>>,
+    // FFT çözünürlüğünde, 0..1 normalize dB
+    linear: Vec<f32>,
+    bin_hz: f32,
+    bands: Vec<Band>,
+    binner: SpectrumBinner,
+}
+
+impl SpectrumAnalyzer {
+    pub fn new(config: &AnalyzerConfig, sample_rate: u32) -> Self {
+        let fft_size = config.fft_size;
+        let window = config.window.coefficients(fft_size);
+        Self {
+            fft: FftPlanner::new().plan_fft_forward(fft_size),
+            // Genlik düzeltmesi: tam ölçekli bir sinüs, pencere ne olursa olsun 0 dB okunur
+            amplitude_scale: 2.0 / window.iter().sum::<f32>(),
+            window,
+            buffer: vec![Complex::new(0.0, 0.0); fft_size],
+            linear: vec![0.0; fft_size / 2],
+            bin_hz: sample_rate as f32 / fft_size as f32,
+            bands: config.bands.clone(),
+            binner: SpectrumBinner::new(&config.spectrum, sample_rate, fft_size),
+        }
+    }
+
+    pub fn bin_hz(&self) -> f32 {
+        self.bin_hz
+    }
+
+    // Son işlenen karenin FFT çözünürlüğündeki spektrumu
+    pub fn spectrum(&self) -> &[f32] {
+        &self.linear
+    }
+
+    // `samples` tam olarak `fft_size` uzunluğunda olmalıdır. Dönen dilim FFT
+    // çözünürlüğündeki spektrumdur; `features` içine yalnızca spektrum ve bantlar yazılır.
+    pub fn process(&mut self, samples: &[f32], features: &mut AudioFeatures) -> &[f32] {
+        self.transform(samples);
+        features.bands.resize(self.bands.len(), 0.0);
+        band_energies(&self.linear, self.bin_hz, &self.bands, &mut features.bands);
+        self.bin(&mut features.spectrum);
+
+        &self.linear
+    }
+
+    // Yalnızca FFT çözünürlüğündeki spektrum; sonuç `spectrum()` ile okunur
+    pub fn transform(&mut self, samples: &[f32]) {
+        for ((value, &sample), &weight) in self.buffer.iter_mut().zip(samples).zip(&self.window) {
+            *value = Complex::new(sample * weight, 0.0);
+        }
+
+        self.fft.process(&mut self.buffer);
+
+        for (level, bin) in self.linear.iter_mut().zip(&self.buffer) {
+            let magnitude = (bin.norm() * self.amplitude_scale).log10() * 20.0;
+            *level = ((magnitude - MIN_DB) / (MAX_DB - MIN_DB)).clamp(0.0, 1.0);
+        }
+    }
+
+    // Son dönüşümün `AnalyzerConfig::spectrum` ölçeğine yeniden örneklenmiş hali
+    pub fn bin(&self, out: &mut Vec<f32>) {
+        self.binner.apply(&self.linear, out);
+    }
+}
+
+// Tek bir pencerenin spektrumu, yeniden örneklenmiş spektrumu ve ham bant enerjileri.
+// Durumsuzdur; aynı pencere her zaman aynı sonucu verir.
+pub fn analyze_frame(samples: &[f32], sample_rate: u32, config: &AnalyzerConfig) -> AudioFeatures {
+    let mut analyzer = SpectrumAnalyzer::new(config, sample_rate);
+    let mut features = AudioFeatures::default();
+    analyzer.process(samples, &mut features);
+    features
+}
+
+// Dışa aktarım için spektrumun birkaç sayıya indirgenmiş hali
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SpectrumSummary {
+    // Güç ağırlıklı ortalama frekans
+    pub centroid_hz: f32,
+    // Gücün %85'inin altında kaldığı frekans
+    pub rolloff_hz: f32,
+    // Geometrik / aritmetik ortalama güç; gürültüde 1'e, tonal seste 0'a yakın
+    pub flatness: f32,
+    pub peak_hz: f32,
+}
+
+// `spectrum` 0..1 normalize dB; hesaplar doğrusal güce geri çevrilerek yapılır
+pub fn summarize_spectrum(spectrum: &[f32], bin_hz: f32) -> SpectrumSummary {
+    if spectrum.is_empty() {
+        return SpectrumSummary::default();
+    }
+
+    let power: Vec<f32> = spectrum
+        .iter()
+        .map(|level| 10f32.powf((MIN_DB + level * (MAX_DB - MIN_DB)) / 10.0))
+        .collect();
+    let total: f32 = power.iter().sum();
+    let centroid = power
+        .iter()
+        .enumerate()
+        .map(|(bin, p)| bin as f32 * p)
+        .sum::<f32>()
+        / total;
+
+    let mut cumulative = 0.0;
+    let rolloff = power
+        .iter()
+        .position(|p| {
+            cumulative += p;
+            cumulative >= total * 0.85
+        })
+        .unwrap_or(power.len() - 1);
+
+    let log_mean = power.iter().map(|p| p.ln()).sum::<f32>() / power.len() as f32;
+    let flatness = log_mean.exp() / (total / power.len() as f32);
+
+    let peak = power
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(bin, _)| bin)
+        .unwrap_or(0);
+
+    SpectrumSummary {
+        centroid_hz: centroid * bin_hz,
+        rolloff_hz: rolloff as f32 * bin_hz,
+        flatness: flatness.clamp(0.0, 1.0),
+        peak_hz: peak as f32 * bin_hz,
+    }
+}
+
+// Tabandaki bin'ler sayılmadan toplam gücün normalize dB karşılığı, 0..1
+pub fn spectrum_loudness(spectrum: &[f32]) -> f32 {
+    let power: f32 = spectrum
+        .iter()
+        .filter(|&&level| level > 0.0)
+        .map(|level| 10f32.powf((MIN_DB + level * (MAX_DB - MIN_DB)) / 10.0))
+        .sum();
+    if power <= 0.0 {
+        return 0.0;
+    }
+    ((10.0 * power.log10() - MIN_DB) / (MAX_DB - MIN_DB)).clamp(0.0, 1.0)
+}
+
+// Spektrumun üstüne zamana bağlı her şey: bant şekillendirme, vuruşlar ve tempo
+pub struct FeatureExtractor {
+    spectrum: SpectrumAnalyzer,
+    stereo: StereoAnalyzer,
+    // Sol ve sağ kanalın ortalaması; bantlar, vuruşlar ve tempo bunun üstünde çalışır
+    mono: Vec<f32>,
+    shapers: Vec<FeatureShaper>,
+    onsets: OnsetDetector,
+    tempo: TempoTracker,
+    chroma: ChromaTracker,
+    new_beats: Vec<BeatEvent>,
+    recent_beats: VecDeque<BeatEvent>,
+}
+
+impl FeatureExtractor {
+    pub fn new(config: &AnalyzerConfig, sample_rate: u32) -> Self {
+        let spectrum = SpectrumAnalyzer::new(config, sample_rate);
+        let hop_seconds = config.hop_size as f64 / sample_rate as f64;
+        Self {
+            shapers: config
+                .shaping
+                .iter()
+                .map(|shaping| FeatureShaper::new(shaping, hop_seconds as f32))
+                .collect(),
+            onsets: OnsetDetector::new(
+                &config.bands,
+                spectrum.bin_hz(),
+                config.fft_size / 2,
+                hop_seconds,
+                config.onset_sensitivity,
+            ),
+            tempo: TempoTracker::new(hop_seconds),
+            chroma: ChromaTracker::new(config, sample_rate),
+            stereo: StereoAnalyzer::new(config, sample_rate),
+            mono: vec![0.0; config.fft_size],
+            spectrum,
+            new_beats: Vec::new(),
+            recent_beats: VecDeque::new(),
+        }
+    }
+
+    pub fn spectrum(&self) -> &SpectrumAnalyzer {
+        &self.spectrum
+    }
+
+    // Son `process` çağrısında bulunan vuruşlar
+    pub fn new_beats(&self) -> &[BeatEvent] {
+        &self.new_beats
+    }
+
+    // Sonraki kare önceki konumun devamı değil; eski vuruşlar bırakılır
+    pub fn seek(&mut self) {
+        self.onsets.seek();
+        self.tempo.seek();
+        self.chroma.seek();
+        self.recent_beats.clear();
+    }
+
+    // Kareler hop aralıklarıyla sırayla verilmelidir; `time` pencere merkezinin zamanıdır.
+    // Mono kaynaklarda `left` ve `right` aynı dilimdir.
+    pub fn process(
+        &mut self,
+        left: &[f32],
+        right: &[f32],
+        time: f64,
+        features: &mut AudioFeatures,
+    ) {
+        for ((mono, &l), &r) in self.mono.iter_mut().zip(left).zip(right) {
+            *mono = (l + r) * 0.5;
+        }
+        self.stereo.process(left, right, &mut features.stereo);
+        let linear = self.spectrum.process(&self.mono, features);
+        self.new_beats.clear();
+
+        for (energy, shaper) in features.bands.iter_mut().zip(&mut self.shapers) {
+            *energy = shaper.process(*energy);
+        }
+        let onset_envelope = self.onsets.process(linear, time, &mut self.new_beats);
+        features.tempo = self.tempo.process(onset_envelope, time);
+        features.key = self.chroma.process(&self.mono, &mut features.chroma);
+
+        self.recent_beats.extend(self.new_beats.iter().copied());
+        while self
+            .recent_beats
+            .front()
+            .is_some_and(|beat| beat.time < time - BEAT_RETENTION)
+        {
+            self.recent_beats.pop_front();
+        }
+        features.beats.clear();
+        features.beats.extend(self.recent_beats.iter().copied());
+        features.timestamp = time;
+    }
+}
+
+// Çalma saatini izleyip halkadan pencereleri okuyan ve her hop'ta bir kare yayımlayan döngü
+pub struct AnalysisDriver {
+    extractor: FeatureExtractor,
+    clock: Arc<PlaybackClock>,
+    ring: Arc<Mutex<SampleRing>>,
+    fft_size: usize,
+    hop_size: i64,
+    latency_frames: i64,
+    sample_rate: u32,
+}
+
+impl AnalysisDriver {
+    pub fn new(
+        config: &AnalyzerConfig,
+        sample_rate: u32,
+        clock: Arc<PlaybackClock>,
+        ring: Arc<Mutex<SampleRing>>,
+    ) -> Self {
+        Self {
+            extractor: FeatureExtractor::new(config, sample_rate),
+            clock,
+            ring,
+            fft_size: config.fft_size,
+            hop_size: config.hop_size as i64,
+            latency_frames: config.latency_ms as i64 * sample_rate as i64 / 1000,
+            sample_rate,
+        }
+    }
+
+    // Saat bitince yayımcı geri döner; sonraki parça aynı okuyucuya yazar
+    pub fn spawn(mut self, mut publisher: FeaturePublisher) -> JoinHandle<FeaturePublisher> {
+        thread::spawn(move || {
+            self.run(&mut publisher);
+            publisher
+        })
+    }
+
+    fn run(&mut self, publisher: &mut FeaturePublisher) {
+        let mut left = vec![0.0; self.fft_size];
+        let mut right = vec![0.0; self.fft_size];
+        let mut next_frame = 0i64;
+        let mut seeks = self.clock.seek_count();
+
+        while !self.clock.is_finished() {
+            // Sayaç saatten önce okunur; arada bir atlama olursa sonraki turda yakalanır
+            let seek_count = self.clock.seek_count();
+            let heard = self.clock.frames() as i64 - self.latency_frames;
+            if seek_count != seeks {
+                seeks = seek_count;
+                next_frame = heard.max(0);
+                self.extractor.seek();
+            }
+            if heard < next_frame {
+                thread::sleep(ANALYSIS_POLL);
+                continue;
+            }
+            // Halkanın dışına düşecek kadar geride kalındıysa bugüne atla
+            if heard - next_frame > RING_CAPACITY as i64 / 2 {
+                next_frame = heard;
+            }
+
+            // Pencere, dinleyicinin o an duyduğu örneğin etrafında ortalanır;
+            // henüz çekilmemiş örnekler olamayacağı için sonu halkanın başına kırpılır
+            {
+                let ring = self.ring.lock().unwrap();
+                let end = (next_frame + self.fft_size as i64 / 2).min(ring.written() as i64);
+                ring.read(end - self.fft_size as i64, &mut left, &mut right);
+            }
+            let time = next_frame as f64 / self.sample_rate as f64;
+            next_frame += self.hop_size;
+
+            self.extractor
+                .process(&left, &right, time, publisher.write());
+            publisher.publish();
+        }
+
+        // Parça bitti: görseller son kareye takılı kalmasın
+        publisher.write().silence();
+        publisher.publish();
+    }
+}
+
+// Parçayı gerçek zaman beklemeden çözer ve her hop için `on_frame`'i çağırır.
+// Canlı analizle aynı halka, aynı pencereleme ve aynı `FeatureExtractor` kullanılır;
+// tek fark, çalma saatini beklemek yerine halkanın hep önden doldurulmasıdır.
+// Parçanın örnekleme hızını döndürür.
+pub fn analyze_file<F>(
+    file_path: &Path,
+    config: &AnalyzerConfig,
+    on_frame: F,
+) -> Result<u32, VisualizerError>
+where
+    F: FnMut(&AudioFeatures, &FeatureExtractor) -> Result<(), VisualizerError>,
+{
+    let (source, _) = open_track(file_path)?;
+    analyze_track(source, config, on_frame)
+}
+
+// Önceden açılmış bir kaynak (parça ya da test sinyali) için `analyze_file`
+pub fn analyze_track<S, F>(
+    source: S,
+    config: &AnalyzerConfig,
+    mut on_frame: F,
+) -> Result<u32, VisualizerError>
+where
+    S: Source<Item = f32>,
+    F: FnMut(&AudioFeatures, &FeatureExtractor) -> Result<(), VisualizerError>,
+{
+    let sample_rate = source.sample_rate();
+    let clock = Arc::new(PlaybackClock::new(source.channels()));
+    let ring = Arc::new(Mutex::new(SampleRing::new(RING_CAPACITY)));
+    let mut tee = TeeSource::new(source, clock.clone(), ring.clone());
+
+    let mut extractor = FeatureExtractor::new(config, sample_rate);
+    let mut features = AudioFeatures::default();
+    let mut left = vec![0.0; config.fft_size];
+    let mut right = vec![0.0; config.fft_size];
+    let mut next_frame = 0i64;
+
+    loop {
+        // Pencerenin sonu halkaya girene ya da parça bitene kadar çöz
+        let end = next_frame + config.fft_size as i64 / 2;
+        while !clock.is_finished() && (ring.lock().unwrap().written() as i64) < end {
+            for _ in 0..DECODE_BATCH {
+                if tee.next().is_none() {
+                    break;
+                }
+            }
+        }
+
+        {
+            let ring = ring.lock().unwrap();
+            if next_frame >= ring.written() as i64 {
+                break;
+            }
+            ring.read(end - config.fft_size as i64, &mut left, &mut right);
+        }
+        let time = next_frame as f64 / sample_rate as f64;
+        next_frame += config.hop_size as i64;
+
+        extractor.process(&left, &right, time, &mut features);
+        on_frame(&features, &extractor)?;
+    }
+
+    Ok(sample_rate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binning::SpectrumScale;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::f32::consts::PI;
+
+    const SAMPLE_RATE: u32 = 44100;
+
+    fn config() -> AnalyzerConfig {
+        AnalyzerConfig {
+            bands: vec![
+                Band::new("low", 20.0, 200.0),
+                Band::new("mid", 200.0, 2000.0),
+                Band::new("high", 2000.0, 20000.0),
+            ],
+            shaping: vec![FeatureShaping::default(); 3],
+            ..AnalyzerConfig::default()
+        }
+    }
+
+    fn sine(frequency: f32, amplitude: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * PI * frequency * i as f32 / SAMPLE_RATE as f32).sin())
+            .collect()
+    }
+
+    fn loudest(values: &[f32]) -> usize {
+        values
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(index, _)| index)
+            .unwrap()
+    }
+
+    #[test]
+    fn silence_is_zero() {
+        let features = analyze_frame(&vec![0.0; 2048], SAMPLE_RATE, &config());
+        assert!(features.bands.iter().all(|&energy| energy == 0.0));
+        assert!(features.spectrum.iter().all(|&level| level == 0.0));
+    }
+
+    #[test]
+    fn full_scale_sine_reads_zero_db_for_every_window() {
+        let config = config();
+        // Bin merkezine denk gelen frekans, sızıntı olmadan tepe bin'i ölçer
+        let bin_hz = SAMPLE_RATE as f32 / config.fft_size as f32;
+        let samples = sine(bin_hz * 93.0, 1.0, config.fft_size);
+
+        for window in [
+            WindowFunction::Rectangular,
+            WindowFunction::Hann,
+            WindowFunction::Hamming,
+            WindowFunction::BlackmanHarris,
+            WindowFunction::FlatTop,
+        ] {
+            let config = AnalyzerConfig {
+                window,
+                ..config.clone()
+            };
+            let mut analyzer = SpectrumAnalyzer::new(&config, SAMPLE_RATE);
+            let spectrum = analyzer.process(&samples, &mut AudioFeatures::default());
+            assert_eq!(loudest(spectrum), 93, "{}", window);
+            assert!(
+                (spectrum[93] - 1.0).abs() < 0.01,
+                "{}: {}",
+                window,
+                spectrum[93]
+            );
+        }
+    }
+
+    #[test]
+    fn half_amplitude_sine_reads_minus_six_db() {
+        let config = config();
+        let bin_hz = SAMPLE_RATE as f32 / config.fft_size as f32;
+        let samples = sine(bin_hz * 93.0, 0.5, config.fft_size);
+        let mut analyzer = SpectrumAnalyzer::new(&config, SAMPLE_RATE);
+        let spectrum = analyzer.process(&samples, &mut AudioFeatures::default());
+        let expected = (-6.02 - MIN_DB) / (MAX_DB - MIN_DB);
+        assert!((spectrum[93] - expected).abs() < 0.01, "{}", spectrum[93]);
+    }
+
+    #[test]
+    fn sines_land_in_their_bands() {
+        let config = config();
+        for (frequency, band) in [(80.0, 0), (700.0, 1), (6000.0, 2)] {
+            let features = analyze_frame(&sine(frequency, 0.8, 2048), SAMPLE_RATE, &config);
+            assert_eq!(
+                loudest(&features.bands),
+                band,
+                "{} Hz: {:?}",
+                frequency,
+                features.bands
+            );
+            for (index, &energy) in features.bands.iter().enumerate() {
+                if index != band {
+                    assert!(
+                        energy < 0.05,
+                        "{} Hz leaks into band {}: {}",
+                        frequency,
+                        index,
+                        energy
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn binned_spectrum_peak_follows_frequency() {
+        let config = config();
+        let peaks: Vec<usize> = [100.0, 440.0, 1760.0, 7040.0]
+            .iter()
+            .map(|&frequency| {
+                loudest(&analyze_frame(&sine(frequency, 0.8, 2048), SAMPLE_RATE, &config).spectrum)
+            })
+            .collect();
+        assert!(
+            peaks.windows(2).all(|pair| pair[0] < pair[1]),
+            "{:?}",
+            peaks
+        );
+        // Log ölçekte oktavlar eşit aralıklıdır
+        let steps: Vec<usize> = peaks.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        assert!(
+            steps.iter().max().unwrap() - steps.iter().min().unwrap() <= 1,
+            "{:?}",
+            steps
+        );
+    }
+
+    #[test]
+    fn narrow_spectrum_ranges_keep_their_bins() {
+        let signal = sine(1150.0, 0.8, 2048);
+        for (scale, min_hz, max_hz, len) in [
+            (SpectrumScale::Log { bins: 8 }, 1.0, 5.0, 8),
+            (SpectrumScale::Mel { bins: 8 }, 1.0, 5.0, 8),
+            (SpectrumScale::ThirdOctave, 1100.0, 1200.0, 1),
+            (SpectrumScale::Log { bins: 8 }, 30000.0, 40000.0, 8),
+        ] {
+            let layout = SpectrumLayout {
+                scale,
+                min_hz,
+                max_hz,
+            };
+            assert!(layout.check(SAMPLE_RATE, 2048).is_err(), "{:?}", layout);
+            let config = AnalyzerConfig {
+                spectrum: layout,
+                ..config()
+            };
+            let spectrum = analyze_frame(&signal, SAMPLE_RATE, &config).spectrum;
+            assert_eq!(spectrum.len(), len, "{:?}", layout);
+            assert!(
+                spectrum.iter().all(|level| level.is_finite()),
+                "{:?}",
+                layout
+            );
+        }
+        // Aralıkta merkez olmasa da en yakın üçte bir oktav bandı 1150 Hz'i kapsar
+        let config = AnalyzerConfig {
+            spectrum: SpectrumLayout {
+                scale: SpectrumScale::ThirdOctave,
+                min_hz: 1100.0,
+                max_hz: 1200.0,
+            },
+            ..config()
+        };
+        let inside = analyze_frame(&signal, SAMPLE_RATE, &config).spectrum[0];
+        let outside = analyze_frame(&sine(300.0, 0.8, 2048), SAMPLE_RATE, &config).spectrum[0];
+        assert!(inside > outside + 0.2, "{} {}", inside, outside);
+    }
+
+    #[test]
+    fn white_noise_is_broadband() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let samples: Vec<f32> = (0..2048).map(|_| rng.gen_range(-0.5..0.5)).collect();
+        let features = analyze_frame(&samples, SAMPLE_RATE, &config());
+        for &energy in &features.bands {
+            assert!(energy > 0.2, "{:?}", features.bands);
+        }
+        let spread = features.bands.iter().fold(0.0f32, |max, &e| max.max(e))
+            - features.bands.iter().fold(1.0f32, |min, &e| min.min(e));
+        assert!(spread < 0.1, "{:?}", features.bands);
+    }
+
+    #[test]
+    fn summary_tells_tones_from_noise() {
+        let config = config();
+        let mut analyzer = SpectrumAnalyzer::new(&config, SAMPLE_RATE);
+        let bin_hz = analyzer.bin_hz();
+
+        analyzer.process(&sine(1000.0, 0.8, 2048), &mut AudioFeatures::default());
+        let tone = summarize_spectrum(analyzer.spectrum(), bin_hz);
+        assert!((tone.peak_hz - 1000.0).abs() <= bin_hz, "{:?}", tone);
+        assert!((tone.centroid_hz - 1000.0).abs() < 100.0, "{:?}", tone);
+        assert!(tone.flatness < 0.05, "{:?}", tone);
+
+        let mut rng = StdRng::seed_from_u64(11);
+        let noise: Vec<f32> = (0..2048).map(|_| rng.gen_range(-0.5..0.5)).collect();
+        analyzer.process(&noise, &mut AudioFeatures::default());
+        let noise = summarize_spectrum(analyzer.spectrum(), bin_hz);
+        assert!(noise.flatness > 0.3, "{:?}", noise);
+        assert!(noise.centroid_hz > 5000.0, "{:?}", noise);
+        assert!(noise.rolloff_hz > noise.centroid_hz, "{:?}", noise);
+    }
+
+    #[test]
+    fn impulse_has_flat_spectrum() {
+        // Küçük FFT, tek örneklik darbenin -60 dB tabanının üstünde kalmasını sağlar
+        let config = AnalyzerConfig {
+            fft_size: 512,
+            hop_size: 256,
+            window: WindowFunction::Rectangular,
+            ..config()
+        };
+        let mut samples = vec![0.0; config.fft_size];
+        samples[config.fft_size / 2] = 1.0;
+        let mut analyzer = SpectrumAnalyzer::new(&config, SAMPLE_RATE);
+        let spectrum = analyzer.process(&samples, &mut AudioFeatures::default());
+        let first = spectrum[1];
+        assert!(first > 0.0);
+        assert!(spectrum[1..]
+            .iter()
+            .all(|level| (level - first).abs() < 1e-4));
+    }
+
+    // Her vuruşta kısa, sönümlenen gürültü patlaması
+    fn click_track(period: usize, seconds: usize) -> Vec<f32> {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut signal = vec![0.0f32; SAMPLE_RATE as usize * seconds];
+        for start in (0..signal.len()).step_by(period) {
+            for (offset, sample) in signal[start..].iter_mut().take(2000).enumerate() {
+                *sample = rng.gen_range(-0.8..0.8) * (-(offset as f32) / 300.0).exp();
+            }
+        }
+        signal
+    }
+
+    #[test]
+    fn impulse_train_fires_beats_and_locks_tempo() {
+        let config = config();
+        let hop = config.hop_size;
+        let bpm = 120.0;
+        let period = (60.0 / bpm * SAMPLE_RATE as f32) as usize;
+        let seconds = 12;
+        let signal = click_track(period, seconds);
+
+        let mut extractor = FeatureExtractor::new(&config, SAMPLE_RATE);
+        let mut features = AudioFeatures::default();
+        let mut beats: Vec<BeatEvent> = Vec::new();
+        let mut frame = 0;
+        while frame + config.fft_size <= signal.len() {
+            let time = (frame + config.fft_size / 2) as f64 / SAMPLE_RATE as f64;
+            let window = &signal[frame..frame + config.fft_size];
+            extractor.process(window, window, time, &mut features);
+            for beat in &features.beats {
+                if !beats.contains(beat) {
+                    beats.push(*beat);
+                }
+            }
+            frame += hop;
+        }
+
+        let high: Vec<f64> = beats
+            .iter()
+            .filter(|beat| beat.band == 2)
+            .map(|beat| beat.time)
+            .collect();
+        // İlk saniye eşik geçmişi dolarken kaçabilir
+        assert!(
+            high.len() >= 2 * seconds - 3,
+            "{} beats: {:?}",
+            high.len(),
+            high
+        );
+        let tolerance = 1.5 * hop as f64 / SAMPLE_RATE as f64;
+        for &time in &high {
+            let nearest = (time * bpm as f64 / 60.0).round() * 60.0 / bpm as f64;
+            assert!(
+                (time - nearest).abs() < tolerance,
+                "beat at {} is off the grid",
+                time
+            );
+        }
+
+        assert!(
+            (features.tempo.bpm - bpm).abs() < 2.0,
+            "{:?}",
+            features.tempo
+        );
+        assert!(features.tempo.confidence > 0.3, "{:?}", features.tempo);
+    }
+
+    #[test]
+    fn beat_phase_follows_a_seek() {
+        let config = config();
+        let period = SAMPLE_RATE as usize / 2;
+        let signal = click_track(period, 40);
+        let mut extractor = FeatureExtractor::new(&config, SAMPLE_RATE);
+        let mut features = AudioFeatures::default();
+        let mut play = |extractor: &mut FeatureExtractor, from: f64, to: f64| {
+            let mut frame = (from * SAMPLE_RATE as f64) as usize;
+            while (frame as f64) < to * SAMPLE_RATE as f64 {
+                let time = (frame + config.fft_size / 2) as f64 / SAMPLE_RATE as f64;
+                let window = &signal[frame..frame + config.fft_size];
+                extractor.process(window, window, time, &mut features);
+                frame += config.hop_size;
+            }
+            (
+                features.tempo,
+                frame + config.fft_size / 2 - config.hop_size,
+            )
+        };
+
+        play(&mut extractor, 0.0, 10.0);
+        // Yarım vuruş kayık bir konuma: eski zarf yeni ızgaraya uymaz
+        extractor.seek();
+        let (tempo, center) = play(&mut extractor, 30.25, 33.5);
+        let expected = (center % period) as f32 / period as f32;
+        let error = (tempo.beat_phase - expected + 0.5).rem_euclid(1.0) - 0.5;
+        assert!(
+            (tempo.bpm - 120.0).abs() < 2.0 && error.abs() < 0.1,
+            "{:?}, expected phase {}",
+            tempo,
+            expected
+        );
+    }
+}
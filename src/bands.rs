@@ -0,0 +1,215 @@
+use std::ops::Range;
+use std::str::FromStr;
+
+// Shader'daki `bands` dizisinin boyu
+pub const MAX_BANDS: usize = 16;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Band {
+    pub name: String,
+    pub low_hz: f32,
+    pub high_hz: f32,
+}
+
+impl Band {
+    pub fn new(name: &str, low_hz: f32, high_hz: f32) -> Self {
+        Self {
+            name: name.to_string(),
+            low_hz,
+            high_hz,
+        }
+    }
+
+    // Merkez frekansı aralığa düşen bin'ler; aralığa hiç bin düşmüyorsa
+    // (küçük FFT, dar bant) en yakın tek bin
+    pub fn bins(&self, bin_hz: f32, bin_count: usize) -> Range<usize> {
+        let first = (self.low_hz / bin_hz).ceil() as usize;
+        let last = ((self.high_hz / bin_hz).ceil() as usize).min(bin_count);
+        if first < last {
+            first..last
+        } else {
+            let center = ((self.low_hz + self.high_hz) / 2.0 / bin_hz).round() as usize;
+            let center = center.min(bin_count.saturating_sub(1));
+            center..(center + 1).min(bin_count)
+        }
+    }
+}
+
+// "isim:alt-üst" biçimi, örn. "kick:60-150"
+impl FromStr for Band {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid band '{}', expected NAME:LOW-HIGH in Hz", spec);
+        let (name, range) = spec.split_once(':').ok_or_else(invalid)?;
+        let (low, high) = range.split_once('-').ok_or_else(invalid)?;
+        let low_hz: f32 = low.trim().parse().map_err(|_| invalid())?;
+        let high_hz: f32 = high.trim().parse().map_err(|_| invalid())?;
+
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!("invalid band name '{}'", name));
+        }
+        if !(low_hz >= 0.0 && high_hz > low_hz && high_hz.is_finite()) {
+            return Err(format!(
+                "band '{}' needs 0 <= LOW < HIGH, got {}-{}",
+                name, low, high
+            ));
+        }
+
+        Ok(Band::new(name, low_hz, high_hz))
+    }
+}
+
+pub fn default_bands() -> Vec<Band> {
+    vec![
+        Band::new("sub", 20.0, 60.0),
+        Band::new("kick", 60.0, 150.0),
+        Band::new("bass", 20.0, 250.0),
+        Band::new("low-mid", 250.0, 500.0),
+        Band::new("mid", 250.0, 2000.0),
+        Band::new("high", 2000.0, 20000.0),
+        Band::new("presence", 4000.0, 6000.0),
+        Band::new("air", 10000.0, 20000.0),
+    ]
+}
+
+// Her bant, kapsadığı bin'lerin ortalamasıdır
+pub fn band_energies(spectrum: &[f32], bin_hz: f32, bands: &[Band], out: &mut [f32]) {
+    for (band, energy) in bands.iter().zip(out.iter_mut()) {
+        let bins = band.bins(bin_hz, spectrum.len());
+        *energy = if bins.is_empty() {
+            0.0
+        } else {
+            spectrum[bins.clone()].iter().sum::<f32>() / bins.len() as f32
+        };
+    }
+}
+
+// Görsellerin banda bağlanabilen parametreleri
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VisualParam {
+    Speed,
+    Scale,
+    LookX,
+    LookY,
+    Red,
+    Green,
+    Blue,
+    ShaderBass,
+    ShaderMid,
+    ShaderHigh,
+    // Vuruş tetikleyicileri
+    Flash,
+    Kick,
+    Spawn,
+}
+
+impl VisualParam {
+    pub const ALL: [VisualParam; 13] = [
+        VisualParam::Speed,
+        VisualParam::Scale,
+        VisualParam::LookX,
+        VisualParam::LookY,
+        VisualParam::Red,
+        VisualParam::Green,
+        VisualParam::Blue,
+        VisualParam::ShaderBass,
+        VisualParam::ShaderMid,
+        VisualParam::ShaderHigh,
+        VisualParam::Flash,
+        VisualParam::Kick,
+        VisualParam::Spawn,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            VisualParam::Speed => "speed",
+            VisualParam::Scale => "scale",
+            VisualParam::LookX => "look-x",
+            VisualParam::LookY => "look-y",
+            VisualParam::Red => "red",
+            VisualParam::Green => "green",
+            VisualParam::Blue => "blue",
+            VisualParam::ShaderBass => "shader-bass",
+            VisualParam::ShaderMid => "shader-mid",
+            VisualParam::ShaderHigh => "shader-high",
+            VisualParam::Flash => "flash",
+            VisualParam::Kick => "kick",
+            VisualParam::Spawn => "spawn",
+        }
+    }
+
+    // Eski sabit bas/orta/tiz davranışına karşılık gelen bağlar
+    fn default_band(self) -> &'static str {
+        match self {
+            VisualParam::Speed
+            | VisualParam::Scale
+            | VisualParam::Blue
+            | VisualParam::ShaderBass => "bass",
+            VisualParam::LookY | VisualParam::Red | VisualParam::ShaderMid => "mid",
+            VisualParam::LookX | VisualParam::Green | VisualParam::ShaderHigh => "high",
+            VisualParam::Flash | VisualParam::Kick => "kick",
+            VisualParam::Spawn => "mid",
+        }
+    }
+}
+
+impl FromStr for VisualParam {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, String> {
+        VisualParam::ALL
+            .iter()
+            .copied()
+            .find(|param| param.name() == name)
+            .ok_or_else(|| {
+                let names: Vec<_> = VisualParam::ALL.iter().map(|p| p.name()).collect();
+                format!(
+                    "unknown visual parameter '{}', expected one of: {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+// Parametre -> bant indisi eşlemesi
+#[derive(Clone, Debug)]
+pub struct BandBindings {
+    indices: [usize; VisualParam::ALL.len()],
+}
+
+impl BandBindings {
+    pub fn resolve(bands: &[Band], overrides: &[(VisualParam, String)]) -> Result<Self, String> {
+        let find = |name: &str| {
+            bands
+                .iter()
+                .position(|band| band.name == name)
+                .ok_or_else(|| format!("no band named '{}'", name))
+        };
+
+        let mut indices = [0; VisualParam::ALL.len()];
+        for (slot, param) in indices.iter_mut().zip(VisualParam::ALL) {
+            let name = overrides
+                .iter()
+                .rev()
+                .find(|(bound, _)| *bound == param)
+                .map_or(param.default_band(), |(_, name)| name.as_str());
+            *slot = find(name).map_err(|err| format!("'{}': {}", param.name(), err))?;
+        }
+
+        Ok(Self { indices })
+    }
+
+    pub fn band(&self, param: VisualParam) -> usize {
+        self.indices[param as usize]
+    }
+
+    pub fn value(&self, param: VisualParam, energies: &[f32]) -> f32 {
+        energies.get(self.band(param)).copied().unwrap_or(0.0)
+    }
+}
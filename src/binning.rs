@@ -0,0 +1,205 @@
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpectrumScale {
+    // FFT bin'leri olduğu gibi
+    Linear,
+    Log { bins: usize },
+    ThirdOctave,
+    Mel { bins: usize },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpectrumLayout {
+    pub scale: SpectrumScale,
+    pub min_hz: f32,
+    pub max_hz: f32,
+}
+
+impl Default for SpectrumLayout {
+    fn default() -> Self {
+        Self {
+            scale: SpectrumScale::Log { bins: 64 },
+            min_hz: 30.0,
+            max_hz: 16000.0,
+        }
+    }
+}
+
+// "linear", "log:64", "third-octave", "mel:40"
+impl FromStr for SpectrumScale {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, String> {
+        let (name, count) = match spec.split_once(':') {
+            Some((name, count)) => (name, Some(count)),
+            None => (spec, None),
+        };
+        let bins = |default: usize| -> Result<usize, String> {
+            match count {
+                None => Ok(default),
+                Some(count) => match count.parse() {
+                    Ok(bins) if (1..=1024).contains(&bins) => Ok(bins),
+                    _ => Err(format!("invalid bin count '{}', expected 1..=1024", count)),
+                },
+            }
+        };
+
+        match name {
+            "linear" if count.is_none() => Ok(SpectrumScale::Linear),
+            "third-octave" if count.is_none() => Ok(SpectrumScale::ThirdOctave),
+            "log" => Ok(SpectrumScale::Log { bins: bins(64)? }),
+            "mel" => Ok(SpectrumScale::Mel { bins: bins(40)? }),
+            _ => Err(format!(
+                "invalid spectrum scale '{}', expected linear, log[:N], third-octave or mel[:N]",
+                spec
+            )),
+        }
+    }
+}
+
+impl SpectrumLayout {
+    // Aralık en az bir FFT bin'i genişliğinde olmalı, Nyquist'i aşmamalı ve
+    // üçte bir oktavda en az bir bant merkezi içermeli
+    pub fn check(&self, sample_rate: u32, fft_size: usize) -> Result<(), String> {
+        let bin_hz = sample_rate as f32 / fft_size as f32;
+        let nyquist = sample_rate as f32 / 2.0;
+        if self.max_hz > nyquist {
+            return Err(format!(
+                "spectrum range {}-{} Hz goes above the Nyquist frequency ({} Hz at {} Hz)",
+                self.min_hz, self.max_hz, nyquist, sample_rate
+            ));
+        }
+        if self.max_hz - self.min_hz < bin_hz {
+            return Err(format!(
+                "spectrum range {}-{} Hz is narrower than one FFT bin ({:.1} Hz at FFT size {})",
+                self.min_hz, self.max_hz, bin_hz, fft_size
+            ));
+        }
+        if self.scale == SpectrumScale::ThirdOctave
+            && third_octave_centers(self.min_hz, self.max_hz).is_empty()
+        {
+            return Err(format!(
+                "spectrum range {}-{} Hz contains no third-octave band center",
+                self.min_hz, self.max_hz
+            ));
+        }
+        Ok(())
+    }
+}
+
+// 1 kHz'e göre standart merkezlerin sıra numaraları: merkez = 1000 * 2^(k/3)
+fn third_octave_centers(min_hz: f32, max_hz: f32) -> std::ops::RangeInclusive<i32> {
+    let first = (3.0 * (min_hz / 1000.0).log2()).ceil() as i32;
+    let last = (3.0 * (max_hz / 1000.0).log2()).floor() as i32;
+    first..=last
+}
+
+fn hz_to_mel(hz: f32) -> f32 {
+    2595.0 * (1.0 + hz / 700.0).log10()
+}
+
+fn mel_to_hz(mel: f32) -> f32 {
+    700.0 * (10f32.powf(mel / 2595.0) - 1.0)
+}
+
+// Doğrusal FFT spektrumunu seçilen ölçekte yeniden örnekler.
+// Her çıkış bin'i, kapsadığı FFT bin'lerinin örtüşme ağırlıklı ortalamasıdır.
+pub struct SpectrumBinner {
+    bins: Binning,
+}
+
+enum Binning {
+    // FFT spektrumu olduğu gibi geçer
+    Linear,
+    // Çıkış bin'i başına (FFT bin'i, ağırlık) listesi; en az bir çıkış bin'i vardır
+    Weighted(Vec<Vec<(usize, f32)>>),
+}
+
+impl SpectrumBinner {
+    pub fn new(layout: &SpectrumLayout, sample_rate: u32, fft_size: usize) -> Self {
+        let bin_hz = sample_rate as f32 / fft_size as f32;
+        let nyquist = sample_rate as f32 / 2.0;
+        let max_hz = layout.max_hz.min(nyquist);
+        // Aralık bir bin'den dar ya da tamamen Nyquist'in üstünde olabilir
+        let min_hz = layout.min_hz.max(bin_hz / 2.0).min(max_hz);
+
+        let edges: Vec<f32> = match layout.scale {
+            SpectrumScale::Linear => {
+                return Self {
+                    bins: Binning::Linear,
+                }
+            }
+            SpectrumScale::Log { bins } => {
+                let ratio = (max_hz / min_hz).powf(1.0 / bins as f32);
+                (0..=bins).map(|i| min_hz * ratio.powi(i as i32)).collect()
+            }
+            SpectrumScale::ThirdOctave => {
+                // Kenarlar merkez * 2^(±1/6); aralıkta merkez yoksa ortasına en yakın bant
+                let mut centers = third_octave_centers(min_hz, max_hz);
+                if centers.is_empty() {
+                    let middle = (1.5 * (min_hz * max_hz / 1e6).log2()).round() as i32;
+                    centers = middle..=middle;
+                }
+                (*centers.start()..=*centers.end() + 1)
+                    .map(|k| 1000.0 * 2f32.powf((k as f32 - 0.5) / 3.0))
+                    .collect()
+            }
+            SpectrumScale::Mel { bins } => {
+                let (low, high) = (hz_to_mel(min_hz), hz_to_mel(max_hz));
+                (0..=bins)
+                    .map(|i| mel_to_hz(low + (high - low) * i as f32 / bins as f32))
+                    .collect()
+            }
+        };
+
+        let fft_bins = fft_size / 2;
+        let weights = edges
+            .windows(2)
+            .map(|edge| {
+                let (low, high) = (edge[0] / bin_hz, edge[1] / bin_hz);
+                let mut taps = Vec::new();
+                if high - low < 1.0 {
+                    // Bir bin'den dar: merkezde doğrusal ara değer
+                    let center = (low + high) / 2.0;
+                    let index = center.floor() as usize;
+                    let fraction = center - index as f32;
+                    taps.push((index, 1.0 - fraction));
+                    taps.push((index + 1, fraction));
+                } else {
+                    for index in low.floor() as usize..=high.ceil() as usize {
+                        let overlap =
+                            (high.min(index as f32 + 0.5) - low.max(index as f32 - 0.5)).max(0.0);
+                        if overlap > 0.0 {
+                            taps.push((index, overlap));
+                        }
+                    }
+                }
+                taps.retain(|&(index, weight)| index < fft_bins && weight > 0.0);
+                let total: f32 = taps.iter().map(|&(_, weight)| weight).sum();
+                taps.iter_mut().for_each(|(_, weight)| *weight /= total);
+                taps
+            })
+            .collect();
+
+        Self {
+            bins: Binning::Weighted(weights),
+        }
+    }
+
+    pub fn apply(&self, spectrum: &[f32], out: &mut Vec<f32>) {
+        out.clear();
+        let weights = match &self.bins {
+            Binning::Linear => {
+                out.extend_from_slice(spectrum);
+                return;
+            }
+            Binning::Weighted(weights) => weights,
+        };
+        out.extend(weights.iter().map(|taps| {
+            taps.iter()
+                .map(|&(index, weight)| spectrum.get(index).copied().unwrap_or(0.0) * weight)
+                .sum::<f32>()
+        }));
+    }
+}
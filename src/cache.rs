@@ -0,0 +1,809 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::UNIX_EPOCH;
+
+use crate::analysis::{
+    analyze_file, analyze_frame, spectrum_loudness, AnalyzerConfig, ANALYSIS_POLL,
+};
+use crate::chroma::{KeyEstimate, PITCH_CLASSES};
+use crate::error::VisualizerError;
+use crate::features::{AudioFeatures, FeaturePublisher, BEAT_RETENTION};
+use crate::onset::BeatEvent;
+use crate::playback::PlaybackClock;
+use crate::stereo::StereoFeatures;
+use crate::tempo::TempoEstimate;
+
+const MAGIC: &[u8; 4] = b"MVFC";
+// Biçim ya da analiz matematiği değişince artırılır; eski önbellekler kendiliğinden geçersizleşir
+const FORMAT_VERSION: u32 = 3;
+// Sihirli sayı, sürüm, anahtar, hop süresi ve üç sayaç
+const HEADER_BYTES: u64 = 36;
+// Kare başına bant ve spektrumdan bağımsız kısım: ses yüksekliği, tempo, stereo
+// özetleri, kroma ve anahtar
+const FRAME_FIXED_BYTES: u64 = 36;
+const BEAT_BYTES: u64 = 12;
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+// Aynı süreçteki geçici dosyalar birbirinin üzerine yazmasın diye
+static NEXT_PARTIAL: AtomicU64 = AtomicU64::new(0);
+// Arka planda doldurulmakta olan kayıtların anahtarları
+static FILLING: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+
+// Anahtar için dosyanın başından ve sonundan okunan bayt sayısı
+const KEY_SAMPLE_BYTES: u64 = 64 * 1024;
+
+// Drop: sakin bir bölümün ardından ses yüksekliğinin belirgin şekilde sıçradığı an
+const DROP_BEFORE_SECONDS: f64 = 8.0;
+const DROP_AFTER_SECONDS: f64 = 4.0;
+// Normalize dB cinsinden, yaklaşık 6 dB
+const DROP_RISE: f32 = 0.1;
+const DROP_MIN_SPACING: f64 = 16.0;
+// Görsellerin drop'u ne kadar önceden sezdiği
+const ANTICIPATION_SECONDS: f64 = 4.0;
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+// Dosyanın boyutu, değişme zamanı, baş ve sonundan birer parça ile sonucu etkileyen
+// analiz ayarlarının özeti. Dosyanın tamamı okunmaz; parça başlarken çağrılır.
+// Gecikme çalma zamanında uygulandığı için anahtara girmez.
+pub fn cache_key(file_path: &Path, config: &AnalyzerConfig) -> io::Result<u64> {
+    let mut file = File::open(file_path)?;
+    let metadata = file.metadata()?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |since| since.as_nanos());
+    let mut hash = fnv1a(FNV_OFFSET, &metadata.len().to_le_bytes());
+    hash = fnv1a(hash, &modified.to_le_bytes());
+
+    let mut chunk = Vec::with_capacity(KEY_SAMPLE_BYTES as usize);
+    for offset in [0, metadata.len().saturating_sub(KEY_SAMPLE_BYTES)] {
+        chunk.clear();
+        file.seek(SeekFrom::Start(offset))?;
+        (&mut file).take(KEY_SAMPLE_BYTES).read_to_end(&mut chunk)?;
+        hash = fnv1a(hash, &chunk);
+    }
+
+    let settings = format!(
+        "{}|{}|{}|{}|{:?}|{:?}|{:?}|{}",
+        FORMAT_VERSION,
+        config.fft_size,
+        config.hop_size,
+        config.window,
+        config.bands,
+        config.shaping,
+        config.spectrum,
+        config.onset_sensitivity
+    );
+    Ok(fnv1a(hash, settings.as_bytes()))
+}
+
+// $XDG_CACHE_HOME/music_vis, yoksa ~/.cache/music_vis
+pub fn default_cache_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .map(|dir| dir.join("music_vis"))
+}
+
+// Bir parçanın hop başına bütün özellikleri. 0..1 değerler diskte nicemlenir:
+// bantlar 16 bit, spektrumlar ve kroma 8 bit; -1..1 değerler 16 bite kaydırılarak yazılır.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeatureTimeline {
+    pub hop_seconds: f64,
+    pub band_count: usize,
+    pub spectrum_len: usize,
+    // Kare başına toplam seviye (normalize dB); drop tespiti için
+    pub loudness: Vec<f32>,
+    // `frame * band_count + band`
+    pub bands: Vec<f32>,
+    // `frame * spectrum_len + bin`
+    pub spectrum: Vec<f32>,
+    pub tempo: Vec<TempoEstimate>,
+    // Kare başına; spektrumları `spectrum_len`, pan'ları `band_count` uzunluğunda
+    pub stereo: Vec<StereoFeatures>,
+    pub chroma: Vec<[f32; PITCH_CLASSES]>,
+    pub key: Vec<KeyEstimate>,
+    pub beats: Vec<BeatEvent>,
+}
+
+impl FeatureTimeline {
+    pub fn frame_count(&self) -> usize {
+        self.loudness.len()
+    }
+
+    // Canlı analizle aynı kareleri tek seferde üretir; `stop` kurulursa her hop'ta
+    // fark edilir ve `VisualizerError::Cancelled` döner
+    pub fn analyze(
+        file_path: &Path,
+        config: &AnalyzerConfig,
+        stop: &AtomicBool,
+    ) -> Result<Self, VisualizerError> {
+        let mut timeline = FeatureTimeline {
+            band_count: config.bands.len(),
+            ..Default::default()
+        };
+        let sample_rate = analyze_file(file_path, config, |features, extractor| {
+            if stop.load(Ordering::Relaxed) {
+                return Err(VisualizerError::Cancelled);
+            }
+            timeline
+                .loudness
+                .push(spectrum_loudness(extractor.spectrum().spectrum()));
+            timeline.bands.extend_from_slice(&features.bands);
+            timeline.spectrum_len = features.spectrum.len();
+            timeline.spectrum.extend_from_slice(&features.spectrum);
+            timeline.tempo.push(features.tempo);
+            timeline.stereo.push(features.stereo.clone());
+            timeline.chroma.push(features.chroma);
+            timeline.key.push(features.key);
+            timeline.beats.extend_from_slice(extractor.new_beats());
+            Ok(())
+        })?;
+        timeline.hop_seconds = config.hop_size as f64 / sample_rate as f64;
+        Ok(timeline)
+    }
+
+    // Her kare için yaklaşan drop'a göre 0..1 beklenti; drop anında 1'e ulaşır
+    pub fn anticipation(&self) -> Vec<f32> {
+        let mut anticipation = vec![0.0; self.frame_count()];
+        for drop in find_drops(&self.loudness, self.hop_seconds) {
+            let lead = (ANTICIPATION_SECONDS / self.hop_seconds).round() as usize;
+            let start = drop.saturating_sub(lead);
+            for (offset, value) in anticipation[start..drop].iter_mut().enumerate() {
+                let progress = 1.0 - (drop - start - offset) as f32 / lead as f32;
+                *value = progress * progress;
+            }
+        }
+        anticipation
+    }
+
+    fn write_to(&self, key: u64, out: &mut impl Write) -> io::Result<()> {
+        out.write_all(MAGIC)?;
+        out.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        out.write_all(&key.to_le_bytes())?;
+        out.write_all(&self.hop_seconds.to_le_bytes())?;
+        for count in [self.frame_count(), self.band_count, self.spectrum_len] {
+            out.write_all(&(count as u32).to_le_bytes())?;
+        }
+
+        for frame in 0..self.frame_count() {
+            out.write_all(&quantize16(self.loudness[frame]).to_le_bytes())?;
+            for &energy in &self.bands[frame * self.band_count..(frame + 1) * self.band_count] {
+                out.write_all(&quantize16(energy).to_le_bytes())?;
+            }
+            let spectrum =
+                &self.spectrum[frame * self.spectrum_len..(frame + 1) * self.spectrum_len];
+            let levels: Vec<u8> = spectrum.iter().map(|&level| quantize8(level)).collect();
+            out.write_all(&levels)?;
+            let tempo = &self.tempo[frame];
+            out.write_all(&tempo.bpm.to_le_bytes())?;
+            for value in [tempo.confidence, tempo.beat_phase, tempo.bar_phase] {
+                out.write_all(&quantize16(value).to_le_bytes())?;
+            }
+            let stereo = &self.stereo[frame];
+            for value in [stereo.mid, stereo.side, stereo.width] {
+                out.write_all(&quantize16(value).to_le_bytes())?;
+            }
+            for &value in std::iter::once(&stereo.balance).chain(&stereo.band_pan) {
+                out.write_all(&quantize16((value + 1.0) * 0.5).to_le_bytes())?;
+            }
+            for spectrum in [&stereo.left_spectrum, &stereo.right_spectrum] {
+                let levels: Vec<u8> = spectrum.iter().map(|&level| quantize8(level)).collect();
+                out.write_all(&levels)?;
+            }
+            out.write_all(&self.chroma[frame].map(quantize8))?;
+            let key = &self.key[frame];
+            out.write_all(&[key.tonic, key.minor as u8])?;
+            out.write_all(&quantize16(key.confidence).to_le_bytes())?;
+        }
+
+        out.write_all(&(self.beats.len() as u32).to_le_bytes())?;
+        for beat in &self.beats {
+            out.write_all(&beat.time.to_le_bytes())?;
+            out.write_all(&(beat.band as u16).to_le_bytes())?;
+            out.write_all(&quantize16(beat.strength).to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    // Anahtar, sürüm ya da kare düzeni (`band_count`, `spectrum_len`) uyuşmazsa None;
+    // dosya bozuksa ya da başlık `len` baytlık dosyaya sığmıyorsa hata.
+    // Başlıktaki sayılar, hiçbir şey ayrılmadan önce bunlarla doğrulanır.
+    fn read_from(
+        key: u64,
+        layout: (usize, usize),
+        len: u64,
+        input: &mut impl Read,
+    ) -> io::Result<Option<Self>> {
+        let mut magic = [0u8; 4];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC || read_u32(input)? != FORMAT_VERSION || read_u64(input)? != key {
+            return Ok(None);
+        }
+
+        let hop_seconds = f64::from_bits(read_u64(input)?);
+        let frames = read_u32(input)? as usize;
+        let band_count = read_u32(input)? as usize;
+        let spectrum_len = read_u32(input)? as usize;
+        if (band_count, spectrum_len) != layout {
+            return Ok(None);
+        }
+        let body = frames as u64 * frame_bytes(band_count, spectrum_len) + 4;
+        if HEADER_BYTES + body > len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "frame count exceeds file length",
+            ));
+        }
+        let mut timeline = FeatureTimeline {
+            hop_seconds,
+            band_count,
+            spectrum_len,
+            ..Default::default()
+        };
+
+        let mut levels = vec![0u8; spectrum_len];
+        let mut chroma = [0u8; PITCH_CLASSES];
+        let mut key = [0u8; 2];
+        for _ in 0..frames {
+            timeline.loudness.push(read_unit16(input)?);
+            for _ in 0..band_count {
+                timeline.bands.push(read_unit16(input)?);
+            }
+            input.read_exact(&mut levels)?;
+            timeline
+                .spectrum
+                .extend(levels.iter().map(|&level| level as f32 / u8::MAX as f32));
+            timeline.tempo.push(TempoEstimate {
+                bpm: f32::from_bits(read_u32(input)?),
+                confidence: read_unit16(input)?,
+                beat_phase: read_unit16(input)?,
+                bar_phase: read_unit16(input)?,
+            });
+            let mut stereo = StereoFeatures {
+                mid: read_unit16(input)?,
+                side: read_unit16(input)?,
+                width: read_unit16(input)?,
+                balance: read_signed16(input)?,
+                ..Default::default()
+            };
+            for _ in 0..band_count {
+                stereo.band_pan.push(read_signed16(input)?);
+            }
+            for spectrum in [&mut stereo.left_spectrum, &mut stereo.right_spectrum] {
+                input.read_exact(&mut levels)?;
+                spectrum.extend(levels.iter().map(|&level| level as f32 / u8::MAX as f32));
+            }
+            timeline.stereo.push(stereo);
+            input.read_exact(&mut chroma)?;
+            timeline
+                .chroma
+                .push(chroma.map(|value| value as f32 / u8::MAX as f32));
+            input.read_exact(&mut key)?;
+            if key[0] as usize >= PITCH_CLASSES {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "key tonic out of range",
+                ));
+            }
+            timeline.key.push(KeyEstimate {
+                tonic: key[0],
+                minor: key[1] != 0,
+                confidence: read_unit16(input)?,
+            });
+        }
+
+        let beats = read_u32(input)? as u64;
+        if HEADER_BYTES + body + beats * BEAT_BYTES > len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "beat count exceeds file length",
+            ));
+        }
+        for _ in 0..beats {
+            let time = f64::from_bits(read_u64(input)?);
+            let band = read_u16(input)? as usize;
+            let strength = read_unit16(input)?;
+            if band >= band_count {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "beat band out of range",
+                ));
+            }
+            timeline.beats.push(BeatEvent {
+                time,
+                strength,
+                band,
+            });
+        }
+
+        Ok(Some(timeline))
+    }
+}
+
+// Bantlar için enerji ve pan, spektrum dilimleri için mono, sol ve sağ seviye
+fn frame_bytes(band_count: usize, spectrum_len: usize) -> u64 {
+    FRAME_FIXED_BYTES + 4 * band_count as u64 + 3 * spectrum_len as u64
+}
+
+fn quantize16(value: f32) -> u16 {
+    (value.clamp(0.0, 1.0) * u16::MAX as f32).round() as u16
+}
+
+fn quantize8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * u8::MAX as f32).round() as u8
+}
+
+fn read_u16(input: &mut impl Read) -> io::Result<u16> {
+    let mut bytes = [0u8; 2];
+    input.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(input: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_unit16(input: &mut impl Read) -> io::Result<f32> {
+    Ok(read_u16(input)? as f32 / u16::MAX as f32)
+}
+
+fn read_signed16(input: &mut impl Read) -> io::Result<f32> {
+    Ok(read_unit16(input)? * 2.0 - 1.0)
+}
+
+// Ses yüksekliğinin önceki birkaç saniyeye göre en çok sıçradığı kareler
+fn find_drops(loudness: &[f32], hop_seconds: f64) -> Vec<usize> {
+    let before = (DROP_BEFORE_SECONDS / hop_seconds).round() as usize;
+    let after = (DROP_AFTER_SECONDS / hop_seconds).round() as usize;
+    let spacing = (DROP_MIN_SPACING / hop_seconds).round() as usize;
+    if loudness.len() < before + after {
+        return Vec::new();
+    }
+
+    let mean = |range: std::ops::Range<usize>| {
+        let len = range.len() as f32;
+        loudness[range].iter().sum::<f32>() / len
+    };
+    let rises: Vec<f32> = (before..=loudness.len() - after)
+        .map(|frame| mean(frame..frame + after) - mean(frame - before..frame))
+        .collect();
+
+    // En güçlü sıçramalar önce seçilir, yakınındaki daha zayıf adaylar elenir
+    let mut candidates: Vec<usize> = (0..rises.len())
+        .filter(|&index| rises[index] > DROP_RISE)
+        .collect();
+    candidates.sort_by(|&a, &b| rises[b].total_cmp(&rises[a]));
+    let mut drops: Vec<usize> = Vec::new();
+    for index in candidates {
+        let frame = index + before;
+        if drops.iter().all(|&drop| drop.abs_diff(frame) >= spacing) {
+            drops.push(frame);
+        }
+    }
+    drops.sort_unstable();
+    drops
+}
+
+// Önbellek dizinindeki tek bir parçanın kaydı
+pub struct FeatureCache {
+    path: PathBuf,
+    key: u64,
+}
+
+impl FeatureCache {
+    pub fn new(dir: &Path, file_path: &Path, config: &AnalyzerConfig) -> io::Result<Self> {
+        let key = cache_key(file_path, config)?;
+        Ok(Self {
+            path: dir.join(format!("{:016x}.mvf", key)),
+            key,
+        })
+    }
+
+    // Kayıt yoksa, eskiyse, bu ayarların kare düzenine uymuyorsa ya da okunamıyorsa None;
+    // önbellek hiçbir zaman zorunlu değildir
+    pub fn load(&self, config: &AnalyzerConfig, sample_rate: u32) -> Option<FeatureTimeline> {
+        // Sessiz bir pencere, bu ayarların ürettiği bant ve spektrum boyutlarını verir
+        let layout = analyze_frame(&vec![0.0; config.fft_size], sample_rate, config);
+        let file = File::open(&self.path).ok()?;
+        let len = file.metadata().ok()?.len();
+        FeatureTimeline::read_from(
+            self.key,
+            (layout.bands.len(), layout.spectrum.len()),
+            len,
+            &mut BufReader::new(file),
+        )
+        .ok()
+        .flatten()
+    }
+
+    // Yarım yazılmış bir dosya okunmasın diye önce geçici dosyaya yazılır. Geçici ad
+    // süreç ve çağrı başına farklıdır; aynı kaydı yazan iki yazıcı birbirini bozmaz.
+    pub fn store(&self, timeline: &FeatureTimeline) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let partial = self.path.with_extension(format!(
+            "mvf.{}-{}.partial",
+            std::process::id(),
+            NEXT_PARTIAL.fetch_add(1, Ordering::Relaxed)
+        ));
+        let result = File::create(&partial).and_then(|file| {
+            let mut out = BufWriter::new(file);
+            timeline.write_to(self.key, &mut out)?;
+            out.into_inner()
+                .map_err(|err| err.into_error())?
+                .sync_all()?;
+            fs::rename(&partial, &self.path)
+        });
+        if result.is_err() {
+            let _ = fs::remove_file(&partial);
+        }
+        result
+    }
+
+    // Parça canlı analiz edilirken önbelleği arka planda doldurur. Aynı kayıt zaten
+    // dolduruluyorsa None; dönen değer bırakılınca analiz durdurulur.
+    pub fn fill_in_background(
+        self,
+        file_path: PathBuf,
+        config: AnalyzerConfig,
+    ) -> Option<CacheFill> {
+        {
+            let mut filling = FILLING.lock().unwrap();
+            if filling.contains(&self.key) {
+                return None;
+            }
+            filling.push(self.key);
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let thread = thread::spawn(move || {
+            let result =
+                FeatureTimeline::analyze(&file_path, &config, &thread_stop).and_then(|timeline| {
+                    self.store(&timeline)
+                        .map_err(|err| VisualizerError::Write(self.path.clone(), err))
+                });
+            FILLING.lock().unwrap().retain(|&key| key != self.key);
+            match result {
+                Ok(()) | Err(VisualizerError::Cancelled) => {}
+                Err(err) => eprintln!("warning: feature cache not written: {}", err),
+            }
+        });
+
+        Some(CacheFill {
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+// Arka plandaki bir önbellek doldurma işi
+pub struct CacheFill {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for CacheFill {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                eprintln!("warning: feature cache thread panicked");
+            }
+        }
+    }
+}
+
+// Önbellekteki kareleri çalma saatine göre yayımlar; analiz yapmaz
+pub struct TimelinePlayer {
+    timeline: FeatureTimeline,
+    anticipation: Vec<f32>,
+    clock: Arc<PlaybackClock>,
+    latency_frames: i64,
+    sample_rate: u32,
+}
+
+impl TimelinePlayer {
+    pub fn new(
+        timeline: FeatureTimeline,
+        config: &AnalyzerConfig,
+        sample_rate: u32,
+        clock: Arc<PlaybackClock>,
+    ) -> Self {
+        Self {
+            anticipation: timeline.anticipation(),
+            timeline,
+            clock,
+            latency_frames: config.latency_ms as i64 * sample_rate as i64 / 1000,
+            sample_rate,
+        }
+    }
+
+    // Saat bitince yayımcı geri döner; sonraki parça aynı okuyucuya yazar
+    pub fn spawn(mut self, mut publisher: FeaturePublisher) -> JoinHandle<FeaturePublisher> {
+        thread::spawn(move || {
+            self.run(&mut publisher);
+            publisher
+        })
+    }
+
+    fn run(&mut self, publisher: &mut FeaturePublisher) {
+        let timeline = &self.timeline;
+        let mut published = None;
+
+        while !self.clock.is_finished() {
+            let heard = self.clock.frames() as i64 - self.latency_frames;
+            let time = heard.max(0) as f64 / self.sample_rate as f64;
+            let frame = ((time / timeline.hop_seconds) as usize).min(timeline.frame_count());
+            if published == Some(frame) || frame == timeline.frame_count() {
+                thread::sleep(ANALYSIS_POLL);
+                continue;
+            }
+            published = Some(frame);
+
+            let features: &mut AudioFeatures = publisher.write();
+            let frame_time = frame as f64 * timeline.hop_seconds;
+            features.timestamp = frame_time;
+            features.bands.clear();
+            features.bands.extend_from_slice(
+                &timeline.bands[frame * timeline.band_count..(frame + 1) * timeline.band_count],
+            );
+            features.spectrum.clear();
+            features.spectrum.extend_from_slice(
+                &timeline.spectrum
+                    [frame * timeline.spectrum_len..(frame + 1) * timeline.spectrum_len],
+            );
+            features.tempo = timeline.tempo[frame];
+            features.stereo.clone_from(&timeline.stereo[frame]);
+            features.chroma = timeline.chroma[frame];
+            features.key = timeline.key[frame];
+            // Vuruşların gerçek zamanları bilindiği için canlı analizdeki bir hop'luk gecikme yok
+            features.beats.clear();
+            features.beats.extend(
+                timeline.beats.iter().copied().filter(|beat| {
+                    beat.time <= frame_time && beat.time > frame_time - BEAT_RETENTION
+                }),
+            );
+            features.anticipation = self.anticipation[frame];
+            publisher.publish();
+        }
+
+        publisher.write().silence();
+        publisher.publish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::tests::write_wav;
+
+    // Testlerdeki zaman çizelgesinin düzeniyle, dosyanın tamamı okunur
+    fn read(key: u64, bytes: &[u8]) -> io::Result<Option<FeatureTimeline>> {
+        FeatureTimeline::read_from(key, (2, 3), bytes.len() as u64, &mut &bytes[..])
+    }
+
+    fn timeline() -> FeatureTimeline {
+        let frames = 40;
+        FeatureTimeline {
+            hop_seconds: 1024.0 / 44100.0,
+            band_count: 2,
+            spectrum_len: 3,
+            loudness: (0..frames)
+                .map(|frame| frame as f32 / frames as f32)
+                .collect(),
+            bands: (0..frames * 2).map(|i| (i % 7) as f32 / 7.0).collect(),
+            spectrum: (0..frames * 3).map(|i| (i % 5) as f32 / 4.0).collect(),
+            tempo: (0..frames)
+                .map(|frame| TempoEstimate {
+                    bpm: 128.0,
+                    confidence: 0.5,
+                    beat_phase: frame as f32 / frames as f32,
+                    bar_phase: 0.25,
+                })
+                .collect(),
+            stereo: (0..frames)
+                .map(|frame| StereoFeatures {
+                    left_spectrum: vec![0.5; 3],
+                    right_spectrum: vec![0.25; 3],
+                    mid: 0.75,
+                    side: 0.25,
+                    width: frame as f32 / frames as f32,
+                    balance: -0.5,
+                    band_pan: vec![-1.0, 0.6],
+                })
+                .collect(),
+            chroma: (0..frames)
+                .map(|frame| {
+                    let mut chroma = [0.25; PITCH_CLASSES];
+                    chroma[frame % PITCH_CLASSES] = 1.0;
+                    chroma
+                })
+                .collect(),
+            key: (0..frames)
+                .map(|frame| KeyEstimate {
+                    tonic: (frame % PITCH_CLASSES) as u8,
+                    minor: frame % 2 == 1,
+                    confidence: 0.7,
+                })
+                .collect(),
+            beats: vec![BeatEvent {
+                time: 0.5,
+                strength: 0.75,
+                band: 1,
+            }],
+        }
+    }
+
+    #[test]
+    fn timeline_round_trips_within_quantization() {
+        let original = timeline();
+        let mut bytes = Vec::new();
+        original.write_to(42, &mut bytes).unwrap();
+        assert_eq!(
+            bytes.len() as u64,
+            HEADER_BYTES + 40 * frame_bytes(2, 3) + 4 + BEAT_BYTES
+        );
+        let loaded = read(42, &bytes).unwrap().unwrap();
+
+        assert_eq!(loaded.frame_count(), original.frame_count());
+        assert_eq!(loaded.hop_seconds, original.hop_seconds);
+        assert_eq!(loaded.beats.len(), 1);
+        assert_eq!(loaded.beats[0].band, 1);
+        for (a, b) in loaded.bands.iter().zip(&original.bands) {
+            assert!((a - b).abs() < 1e-4);
+        }
+        for (a, b) in loaded.spectrum.iter().zip(&original.spectrum) {
+            assert!((a - b).abs() < 0.01);
+        }
+        assert_eq!(loaded.tempo[3].bpm, 128.0);
+        let (a, b) = (&loaded.stereo[7], &original.stereo[7]);
+        assert!((a.width - b.width).abs() < 1e-4);
+        assert!((a.balance - b.balance).abs() < 1e-4);
+        assert!((a.band_pan[0] + 1.0).abs() < 1e-4 && (a.band_pan[1] - 0.6).abs() < 1e-4);
+        assert!((a.right_spectrum[2] - 0.25).abs() < 0.01);
+        assert_eq!(loaded.chroma[7][7], 1.0);
+        assert!((loaded.chroma[7][0] - 0.25).abs() < 0.01);
+        assert_eq!((loaded.key[7].tonic, loaded.key[7].minor), (7, true));
+        assert!((loaded.key[7].confidence - 0.7).abs() < 1e-4);
+    }
+
+    #[test]
+    fn stale_key_is_a_miss() {
+        let mut bytes = Vec::new();
+        timeline().write_to(1, &mut bytes).unwrap();
+        assert!(read(2, &bytes).unwrap().is_none());
+        assert!(read(1, &bytes[..bytes.len() - 3]).is_err());
+    }
+
+    #[test]
+    fn mismatched_layout_is_a_miss() {
+        let mut bytes = Vec::new();
+        timeline().write_to(1, &mut bytes).unwrap();
+        let len = bytes.len() as u64;
+        assert!(
+            FeatureTimeline::read_from(1, (2, 4), len, &mut bytes.as_slice())
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            FeatureTimeline::read_from(1, (3, 3), len, &mut bytes.as_slice())
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn oversized_counts_are_rejected_before_reading() {
+        let mut bytes = Vec::new();
+        timeline().write_to(1, &mut bytes).unwrap();
+        // Kare sayısı sihirli sayı, sürüm, anahtar ve hop süresinin ardından gelir
+        let mut huge = bytes.clone();
+        huge[24..28].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(read(1, &huge).is_err());
+
+        // Vuruş sayısı karelerin hemen ardından gelir
+        let beats = bytes.len() - 4 - BEAT_BYTES as usize;
+        bytes[beats..beats + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(read(1, &bytes).is_err());
+    }
+
+    #[test]
+    fn key_follows_the_tail_and_the_settings() {
+        let path = std::env::temp_dir().join(format!("cache-key-{}.bin", std::process::id()));
+        let mut bytes = vec![7u8; 3 * KEY_SAMPLE_BYTES as usize];
+        fs::write(&path, &bytes).unwrap();
+        let config = AnalyzerConfig::default();
+        let key = cache_key(&path, &config).unwrap();
+        assert_eq!(cache_key(&path, &config).unwrap(), key);
+
+        // Boyut ve zaman aynı kalsa bile son parçadaki bir değişiklik yakalanır
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+        *bytes.last_mut().unwrap() = 8;
+        fs::write(&path, &bytes).unwrap();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        let changed = cache_key(&path, &config).unwrap();
+        assert_ne!(changed, key);
+
+        let other = AnalyzerConfig {
+            hop_size: config.hop_size / 2,
+            ..config.clone()
+        };
+        let rehopped = cache_key(&path, &other).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_ne!(rehopped, changed);
+    }
+
+    #[test]
+    fn background_fill_is_deduplicated_and_cancelled_on_drop() {
+        let dir = std::env::temp_dir().join(format!("cache-fill-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let track = dir.join("track.wav");
+        write_wav(&track, 44100, 44100 * 60);
+        let config = AnalyzerConfig::default();
+        let open = || FeatureCache::new(&dir, &track, &config).unwrap();
+        let record = open().path;
+
+        let fill = open().fill_in_background(track.clone(), config.clone());
+        assert!(fill.is_some());
+        assert!(open()
+            .fill_in_background(track.clone(), config.clone())
+            .is_none());
+
+        // Bırakılan iş durur ve ne kayıt ne de geçici dosya bırakır
+        drop(fill);
+        assert!(!record.exists());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        let again = open().fill_in_background(track.clone(), config.clone());
+        assert!(again.is_some());
+        drop(again);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn anticipation_ramps_up_to_the_drop() {
+        let hop_seconds = 0.05;
+        // 20 sn sakin, sonra yüksek
+        let loudness: Vec<f32> = (0..800)
+            .map(|frame| if frame < 400 { 0.3 } else { 0.6 })
+            .collect();
+        assert_eq!(find_drops(&loudness, hop_seconds), vec![400]);
+
+        let timeline = FeatureTimeline {
+            hop_seconds,
+            loudness,
+            ..Default::default()
+        };
+        let anticipation = timeline.anticipation();
+        assert_eq!(anticipation[300], 0.0);
+        assert!(anticipation[330] > 0.0 && anticipation[330] < anticipation[390]);
+        assert!(anticipation[399] > 0.9);
+        assert_eq!(anticipation[400], 0.0);
+    }
+}
@@ -0,0 +1,166 @@
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{Device, FromSample, Host, SampleFormat, SizedSample, Stream, StreamConfig};
+
+use crate::error::VisualizerError;
+use crate::live::{LiveFeed, LiveInput, LiveSource};
+
+fn capture_error(message: impl std::fmt::Display) -> VisualizerError {
+    VisualizerError::Capture(message.to_string())
+}
+
+// Ad verilmezse varsayılan ana makine (Linux'ta ALSA; PulseAudio ve PipeWire
+// onun üzerinden "pulse"/"pipewire" aygıtı olarak görünür)
+fn find_host(name: Option<&str>) -> Result<Host, VisualizerError> {
+    let Some(name) = name else {
+        return Ok(cpal::default_host());
+    };
+    let id = cpal::available_hosts()
+        .into_iter()
+        .find(|id| id.name().eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            let known: Vec<&str> = cpal::available_hosts().iter().map(|id| id.name()).collect();
+            capture_error(format!(
+                "unknown audio host '{}', available: {}",
+                name,
+                known.join(", ")
+            ))
+        })?;
+    cpal::host_from_id(id).map_err(capture_error)
+}
+
+// "default" ana makinenin varsayılan girişidir; aksi halde önce tam ad, sonra
+// büyük/küçük harf duyarsız ad parçası aranır
+fn find_device(host: &Host, name: &str) -> Result<Device, VisualizerError> {
+    if name == "default" {
+        return host
+            .default_input_device()
+            .ok_or_else(|| capture_error("no default input device"));
+    }
+
+    let devices: Vec<(String, Device)> = host
+        .input_devices()
+        .map_err(capture_error)?
+        .filter_map(|device| Some((device.name().ok()?, device)))
+        .collect();
+    let wanted = name.to_lowercase();
+    let index = devices
+        .iter()
+        .position(|(device_name, _)| device_name == name)
+        .or_else(|| {
+            devices
+                .iter()
+                .position(|(device_name, _)| device_name.to_lowercase().contains(&wanted))
+        })
+        .ok_or_else(|| {
+            capture_error(format!(
+                "no input device matching '{}' (see --list-devices)",
+                name
+            ))
+        })?;
+    Ok(devices.into_iter().nth(index).unwrap().1)
+}
+
+// `--list-devices` çıktısı: her ana makine ve giriş aygıtları
+pub fn list_devices(host: Option<&str>) -> Result<(), VisualizerError> {
+    let hosts = match host {
+        Some(name) => vec![find_host(Some(name))?],
+        None => cpal::available_hosts()
+            .into_iter()
+            .filter_map(|id| cpal::host_from_id(id).ok())
+            .collect(),
+    };
+
+    for host in hosts {
+        println!("{}:", host.id().name());
+        let default_name = host
+            .default_input_device()
+            .and_then(|device| device.name().ok());
+        let devices = match host.input_devices() {
+            Ok(devices) => devices,
+            Err(err) => {
+                println!("  (cannot list devices: {})", err);
+                continue;
+            }
+        };
+        for device in devices {
+            let Ok(name) = device.name() else {
+                continue;
+            };
+            let marker = if Some(&name) == default_name.as_ref() {
+                " (default)"
+            } else {
+                ""
+            };
+            match device.default_input_config() {
+                Ok(config) => println!(
+                    "  {}{}: {} ch, {} Hz",
+                    name,
+                    marker,
+                    config.channels(),
+                    config.sample_rate().0
+                ),
+                Err(_) => println!("  {}{}", name, marker),
+            }
+        }
+    }
+    Ok(())
+}
+
+// Aygıtı kendi varsayılan biçimiyle açar; örnekler f32'ye çevrilip kaynağa akar.
+// Dönen giriş açık kaldıkça yakalama sürer.
+pub fn open_capture(
+    host: Option<&str>,
+    device: &str,
+) -> Result<(LiveInput, LiveSource), VisualizerError> {
+    let host = find_host(host)?;
+    let device = find_device(&host, device)?;
+    let device_name = device.name().unwrap_or_else(|_| "input".to_string());
+    let supported = device.default_input_config().map_err(capture_error)?;
+    let config: StreamConfig = supported.config();
+
+    let (source, feed) = LiveSource::new(config.channels, config.sample_rate.0);
+    let stream = match supported.sample_format() {
+        SampleFormat::I8 => build_stream::<i8>(&device, &config, feed.clone()),
+        SampleFormat::I16 => build_stream::<i16>(&device, &config, feed.clone()),
+        SampleFormat::I32 => build_stream::<i32>(&device, &config, feed.clone()),
+        SampleFormat::U8 => build_stream::<u8>(&device, &config, feed.clone()),
+        SampleFormat::U16 => build_stream::<u16>(&device, &config, feed.clone()),
+        SampleFormat::U32 => build_stream::<u32>(&device, &config, feed.clone()),
+        SampleFormat::F32 => build_stream::<f32>(&device, &config, feed.clone()),
+        SampleFormat::F64 => build_stream::<f64>(&device, &config, feed.clone()),
+        format => {
+            return Err(capture_error(format!(
+                "unsupported sample format {}",
+                format
+            )))
+        }
+    }?;
+    stream.play().map_err(capture_error)?;
+
+    Ok((
+        LiveInput::new(feed, Some(Box::new(stream)), device_name),
+        source,
+    ))
+}
+
+fn build_stream<T>(
+    device: &Device,
+    config: &StreamConfig,
+    feed: LiveFeed,
+) -> Result<Stream, VisualizerError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    device
+        .build_input_stream(
+            config,
+            // Örnekler havuzdan gelen bir parçaya yazılır; havuz boşsa blok atılır
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                feed.push(data.iter().map(|sample| sample.to_sample::<f32>()));
+            },
+            |err| eprintln!("warning: capture stream: {}", err),
+            None,
+        )
+        .map_err(capture_error)
+}
@@ -0,0 +1,248 @@
+use std::fmt;
+use std::ops::Range;
+
+use crate::analysis::{AnalyzerConfig, SpectrumAnalyzer, MAX_DB, MIN_DB};
+use crate::window::WindowFunction;
+
+pub const PITCH_CLASSES: usize = 12;
+pub const NOTE_NAMES: [&str; PITCH_CLASSES] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+// Kroma kendi, daha uzun penceresiyle hesaplanır: 2048 örneklik bir FFT'de
+// 200 Hz altındaki komşu yarım tonlar aynı tepeye düşer
+const CHROMA_FFT_SIZE: usize = 8192;
+// Kromaya giren frekans aralığı; altında bin'ler bir yarım tondan geniştir,
+// üstünde çoğunlukla harmonikler ve vurmalılar vardır
+const CHROMA_LOW_HZ: f32 = 80.0;
+const CHROMA_HIGH_HZ: f32 = 5000.0;
+// Bir tepenin sayılması için çevresinin (ana lobun dışındaki bin'lerin) ortalamasını
+// ne kadar aşması gerektiği, dB; gürültünün rastgele tepeleri bunun altında kalır
+const PEAK_PROMINENCE_DB: f32 = 20.0;
+const PEAK_SURROUNDING: Range<usize> = 3..9;
+// Anahtar tahmininin hafızası; kroma bu zaman sabitiyle unutulur
+const KEY_SECONDS: f64 = 10.0;
+// Krumhansl-Kessler ton profilleri, tonikten başlayarak
+const MAJOR_PROFILE: [f32; PITCH_CLASSES] = [
+    6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+];
+const MINOR_PROFILE: [f32; PITCH_CLASSES] = [
+    6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct KeyEstimate {
+    // Tonik perde sınıfı, 0 = C
+    pub tonic: u8,
+    pub minor: bool,
+    // 0..1, biriken kroma ile ton profilinin ilintisi; tonal olmayan müzikte sıfıra yakın
+    pub confidence: f32,
+}
+
+impl fmt::Display for KeyEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = if self.minor { "minor" } else { "major" };
+        write!(
+            f,
+            "{} {}",
+            NOTE_NAMES[self.tonic as usize % PITCH_CLASSES],
+            mode
+        )
+    }
+}
+
+// Spektrum tepelerinden perde sınıfı enerjileri ve bunların birikiminden anahtar
+pub struct ChromaTracker {
+    analyzer: SpectrumAnalyzer,
+    // Son `CHROMA_FFT_SIZE` mono örnek; her karede pencerenin yalnızca yeni hop'u eklenir
+    history: Vec<f32>,
+    hop_size: usize,
+    bins: Range<usize>,
+    // Sönümlü kroma birikimi, her kare toplamı 1 olacak şekilde eklenir
+    profile: [f32; PITCH_CLASSES],
+    decay: f32,
+}
+
+impl ChromaTracker {
+    pub fn new(config: &AnalyzerConfig, sample_rate: u32) -> Self {
+        let fft_size = config.fft_size.max(CHROMA_FFT_SIZE);
+        let analyzer = SpectrumAnalyzer::new(
+            &AnalyzerConfig {
+                fft_size,
+                window: WindowFunction::Hann,
+                ..config.clone()
+            },
+            sample_rate,
+        );
+        let bin_hz = analyzer.bin_hz();
+        let low = ((CHROMA_LOW_HZ / bin_hz).floor() as usize).max(PEAK_SURROUNDING.end);
+        let high =
+            ((CHROMA_HIGH_HZ / bin_hz).ceil() as usize).min(fft_size / 2 - PEAK_SURROUNDING.end);
+        let hop_seconds = config.hop_size as f64 / sample_rate as f64;
+        Self {
+            analyzer,
+            history: vec![0.0; fft_size],
+            hop_size: config.hop_size,
+            bins: low..high.max(low),
+            profile: [0.0; PITCH_CLASSES],
+            decay: (-hop_seconds / KEY_SECONDS).exp() as f32,
+        }
+    }
+
+    // Atlamadan sonra eski örnekler yeni konumun penceresine karışmasın;
+    // anahtar birikimi parça boyunca korunur
+    pub fn seek(&mut self) {
+        self.history.iter_mut().for_each(|sample| *sample = 0.0);
+    }
+
+    // `window` analiz penceresidir (mono); kareler hop aralıklarıyla sırayla verilmelidir.
+    // `chroma` en güçlü perde sınıfı 1 olacak şekilde yazılır; sessizlikte hepsi 0'dır.
+    pub fn process(&mut self, window: &[f32], chroma: &mut [f32; PITCH_CLASSES]) -> KeyEstimate {
+        let fresh = &window[window.len() - self.hop_size.min(window.len())..];
+        self.history.drain(..fresh.len());
+        self.history.extend_from_slice(fresh);
+        self.analyzer.transform(&self.history);
+        let (spectrum, bin_hz) = (self.analyzer.spectrum(), self.analyzer.bin_hz());
+
+        *chroma = [0.0; PITCH_CLASSES];
+        // Yalnızca çevresinden belirgin yerel tepeler sayılır; pencerenin yan lobları
+        // komşu perdelere taşmasın, gürültü de kromaya karışmasın. Tepenin frekansı
+        // parabolik aradeğerlemeyle bin'den daha ince bulunur.
+        let prominence = PEAK_PROMINENCE_DB / (MAX_DB - MIN_DB);
+        for bin in self.bins.clone() {
+            let (before, level, after) = (spectrum[bin - 1], spectrum[bin], spectrum[bin + 1]);
+            if level <= 0.0 || level <= before || level < after {
+                continue;
+            }
+            let surrounding: f32 = PEAK_SURROUNDING
+                .map(|offset| spectrum[bin - offset] + spectrum[bin + offset])
+                .sum::<f32>()
+                / (2 * PEAK_SURROUNDING.len()) as f32;
+            if level - surrounding < prominence {
+                continue;
+            }
+            let curvature = before - 2.0 * level + after;
+            let offset = if curvature < 0.0 {
+                0.5 * (before - after) / curvature
+            } else {
+                0.0
+            };
+            let hz = (bin as f32 + offset) * bin_hz;
+            let pitch = (12.0 * (hz / 440.0).log2()).round() as i32 + 9;
+            // Genlik; güç kullanılırsa en yüksek nota kromanın tamamını ezer
+            let amplitude = 10f32.powf((MIN_DB + level * (MAX_DB - MIN_DB)) / 20.0);
+            chroma[pitch.rem_euclid(PITCH_CLASSES as i32) as usize] += amplitude;
+        }
+
+        let total: f32 = chroma.iter().sum();
+        if total > 0.0 {
+            for (accumulated, &value) in self.profile.iter_mut().zip(chroma.iter()) {
+                *accumulated = *accumulated * self.decay + value / total;
+            }
+            let peak = chroma.iter().copied().fold(0.0, f32::max);
+            chroma.iter_mut().for_each(|value| *value /= peak);
+        }
+        self.estimate()
+    }
+
+    // 24 anahtarın profilleri arasında birikimle en iyi ilintili olan
+    fn estimate(&self) -> KeyEstimate {
+        if self.profile.iter().all(|&value| value <= 0.0) {
+            return KeyEstimate::default();
+        }
+        let mut best = KeyEstimate::default();
+        let mut best_correlation = f32::NEG_INFINITY;
+        for (minor, template) in [(false, &MAJOR_PROFILE), (true, &MINOR_PROFILE)] {
+            for tonic in 0..PITCH_CLASSES {
+                let rotated: Vec<f32> = (0..PITCH_CLASSES)
+                    .map(|pitch| template[(pitch + PITCH_CLASSES - tonic) % PITCH_CLASSES])
+                    .collect();
+                let correlation = pearson(&self.profile, &rotated);
+                if correlation > best_correlation {
+                    best_correlation = correlation;
+                    best = KeyEstimate {
+                        tonic: tonic as u8,
+                        minor,
+                        confidence: correlation.clamp(0.0, 1.0),
+                    };
+                }
+            }
+        }
+        best
+    }
+}
+
+fn pearson(a: &[f32], b: &[f32]) -> f32 {
+    let len = a.len() as f32;
+    let (mean_a, mean_b) = (a.iter().sum::<f32>() / len, b.iter().sum::<f32>() / len);
+    let (mut covariance, mut variance_a, mut variance_b) = (0.0, 0.0, 0.0);
+    for (&x, &y) in a.iter().zip(b) {
+        covariance += (x - mean_a) * (y - mean_b);
+        variance_a += (x - mean_a) * (x - mean_a);
+        variance_b += (y - mean_b) * (y - mean_b);
+    }
+    if variance_a <= 0.0 || variance_b <= 0.0 {
+        0.0
+    } else {
+        covariance / (variance_a * variance_b).sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    // Notaların sinüslerinden oluşan birkaç saniyelik sesin son kroması ve anahtarı
+    fn analyze(notes: &[f32]) -> ([f32; PITCH_CLASSES], KeyEstimate) {
+        let config = AnalyzerConfig::default();
+        let signal: Vec<f32> = (0..44100 * 2)
+            .map(|i| {
+                let t = i as f32 / 44100.0;
+                notes
+                    .iter()
+                    .map(|hz| (2.0 * PI * hz * t).sin())
+                    .sum::<f32>()
+                    * 0.2
+            })
+            .collect();
+        let mut tracker = ChromaTracker::new(&config, 44100);
+        let mut chroma = [0.0; PITCH_CLASSES];
+        let mut key = KeyEstimate::default();
+        for window in signal.windows(config.fft_size).step_by(config.hop_size) {
+            key = tracker.process(window, &mut chroma);
+        }
+        (chroma, key)
+    }
+
+    fn loudest(chroma: &[f32]) -> Vec<usize> {
+        let mut classes: Vec<usize> = (0..PITCH_CLASSES).collect();
+        classes.sort_by(|&a, &b| chroma[b].total_cmp(&chroma[a]));
+        classes.truncate(3);
+        classes.sort_unstable();
+        classes
+    }
+
+    #[test]
+    fn triads_light_up_their_pitch_classes() {
+        // La majör: A C# E, bir oktav aşağıdaki A ile
+        let (chroma, key) = analyze(&[110.0, 220.0, 277.18, 329.63]);
+        assert_eq!(loudest(&chroma), vec![1, 4, 9]);
+        assert_eq!(key.to_string(), "A major");
+        assert!(key.confidence > 0.5, "{}", key.confidence);
+
+        // Re minör: D F A; komşu notalar 2048 örneklik FFT'nin bir bin'inden yakındır
+        let (chroma, key) = analyze(&[73.42, 146.83, 174.61, 220.0]);
+        assert_eq!(loudest(&chroma), vec![2, 5, 9]);
+        assert_eq!(key.to_string(), "D minor");
+    }
+
+    #[test]
+    fn silence_has_no_key() {
+        let config = AnalyzerConfig::default();
+        let mut tracker = ChromaTracker::new(&config, 44100);
+        let mut chroma = [1.0; PITCH_CLASSES];
+        let key = tracker.process(&vec![0.0; config.fft_size], &mut chroma);
+        assert_eq!(chroma, [0.0; PITCH_CLASSES]);
+        assert_eq!(key, KeyEstimate::default());
+    }
+}
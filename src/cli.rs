@@ -0,0 +1,778 @@
+use std::path::PathBuf;
+
+use crate::analysis::AnalyzerConfig;
+use crate::bands::{default_bands, Band, BandBindings, VisualParam, MAX_BANDS};
+use crate::binning::{SpectrumLayout, SpectrumScale};
+use crate::cache::default_cache_dir;
+use crate::envelope::{parse_attack_release, FeatureShaping};
+use crate::export::ExportFormat;
+use crate::output::OutputMode;
+use crate::pcm::PcmConfig;
+use crate::playlist::RepeatMode;
+use crate::scope::Scene;
+use crate::signal::{Signal, SIGNAL_RATE};
+use crate::window::WindowFunction;
+
+// Hızı önceden bilinmeyen girişlerde `--spectrum-range` bu hıza göre denetlenir
+const REFERENCE_SAMPLE_RATE: u32 = 48000;
+// Daha sık hop'lar pencereleri neredeyse tamamen örtüştürür ve analizi boşuna katlar
+const MIN_HOP_DIVISOR: usize = 16;
+
+pub const USAGE: &str = "\
+Usage: music_vis [OPTIONS] <AUDIO_FILE|DIR|PLAYLIST>...
+       music_vis [OPTIONS] --capture <DEVICE> [--host <HOST>]
+       music_vis [OPTIONS] --pcm <PATH|-> [--pcm-format <FORMAT>] ...
+       music_vis [OPTIONS] --signal <SIGNAL> [--duration <SECONDS>]
+       music_vis --list-devices [--host <HOST>]
+       music_vis analyze [OPTIONS] <AUDIO_FILE> [--out <PATH>] [--format <FORMAT>]
+       music_vis analyze [OPTIONS] --signal <SIGNAL> [--duration <SECONDS>] ...
+
+Without a command the files are played and visualized one after another.
+Directories are searched recursively and .m3u, .m3u8 and .pls playlists are
+expanded. 'analyze' decodes the file as fast as possible and writes the
+per-hop features the visualizer would see (bands, spectrum summary, stereo width,
+balance and per-band pan, chroma and key, onsets, tempo) instead of opening a window.
+
+With --capture a live input (a DJ mixer, or a monitor/null source) is analyzed
+instead of files; it is not played back. PulseAudio and PipeWire sources are
+reached through the ALSA host's 'pulse' and 'pipewire' devices.
+
+With --pcm raw interleaved samples are read from a file, a named pipe or
+stdin ('-') and analyzed in real time, e.g. MPD's fifo output:
+  audio_output { type \"fifo\" name \"vis\" path \"/tmp/mpd.fifo\" format \"44100:16:2\" }
+
+--signal plays a generated test signal through the same analysis, to check
+band boundaries and beat detection:
+  sine[:HZ]                    Sine wave (default: 440)
+  sweep[:LOW-HIGH[:SECONDS]]   Repeating logarithmic sweep (default: 20-20000:10)
+  white, pink                  White or pink noise
+  impulse[:BPM]                Short clicks at a tempo (default: 120)
+  chord[:HZ,HZ,...]            Sum of sines (default: 220,277.18,329.63)
+
+Supported audio: MP3, FLAC, WAV/AIFF, Ogg Vorbis, AAC/ALAC in MP4/M4A, and
+Matroska/WebM or CAF containers with those codecs.
+
+Options:
+  --width <PIXELS>      Window width (default: 800)
+  --height <PIXELS>     Window height (default: 600)
+  --fullscreen          Open fullscreen on the primary monitor
+  --scene <NAME>        tunnel, or vectorscope: a phosphor goniometer of the
+                        left/right samples (default: tunnel)
+  --fft-size <N>        FFT size, a power of two in 512..=16384 (default: 2048)
+  --hop-size <N>        Frames between analysis windows, from 1/16 of the FFT
+                        size up to the FFT size (default: half the FFT size)
+  --window <NAME>       Analysis window: rectangular, hann, hamming,
+                        blackman-harris, flat-top (default: hann)
+  --seed <N>            Seed for the shape layout (default: random)
+  --volume <GAIN>       Playback volume, 1.0 is unchanged (default: 1.0)
+  --band <NAME:LOW-HIGH> Add a frequency band in Hz, or redefine a built-in one
+                        (built-in: sub, kick, bass, low-mid, mid, high,
+                        presence, air); may be repeated
+  --bind <PARAM=BAND>   Drive a visual parameter from a band: speed, scale,
+                        look-x, look-y, red, green, blue, shader-bass,
+                        shader-mid, shader-high, or fire a beat trigger
+                        from its onsets: flash, kick, spawn; may be repeated
+  --spectrum <SCALE>    Published spectrum: linear, log[:N], third-octave or
+                        mel[:N] (default: log:64)
+  --spectrum-range <MIN-MAX>
+                        Frequency range of the spectrum in Hz
+                        (default: 30-16000)
+  --smooth <BAND=ATTACK:RELEASE>
+                        Attack and release time in ms for a band's value, or
+                        for every band with 'all' (default: all=10:150)
+  --agc <BAND=SECONDS>  Normalize a band (or 'all') to 0..1 over the last
+                        SECONDS of the track; 0 disables (default: all=10)
+  --onset-sensitivity <K>
+                        Beat threshold in standard deviations above the
+                        recent spectral flux; lower fires more (default: 1.5)
+  --latency <MS>        Output latency to compensate in the analysis window;
+                        negative values make the visuals lead (default: 0)
+  --no-audio-output     Analyze without playing the audio (no sound card needed)
+  --speed <FACTOR>      Clock speed of the silent output, 2.0 runs twice
+                        as fast as real time (default: 1.0)
+  --cache-dir <DIR>     Where analyzed tracks are kept so later runs replay
+                        them instead of re-analyzing
+                        (default: $XDG_CACHE_HOME/music_vis or ~/.cache/music_vis)
+  --no-cache            Always analyze live and do not write the cache
+  --capture <DEVICE>    Analyze a live input device: 'default', or a name or
+                        part of one as shown by --list-devices
+  --host <HOST>         Audio host for capture: alsa, jack, ...
+                        (default: the platform default)
+  --list-devices        List the audio hosts and their input devices
+  --pcm <PATH|->        Analyze raw PCM from a file, named pipe or stdin
+  --pcm-format <FORMAT> u8, s16le, s16be, s24le, s32le or f32le
+                        (default: s16le)
+  --pcm-rate <HZ>       Sample rate of the raw PCM (default: 44100)
+  --pcm-channels <N>    Interleaved channels of the raw PCM (default: 2)
+  --signal <SIGNAL>     Play a generated test signal instead of files
+  --duration <SECONDS>  Length of the test signal (default: endless, or 10
+                        seconds with 'analyze')
+  --shuffle             Play the tracks in random order
+  --repeat <MODE>       off, all (the whole list) or one (the current track)
+                        (default: off)
+  -h, --help            Print this help
+
+Analyze options:
+  --out <PATH>          Feature timeline file, '-' for stdout (default: -)
+  --format <FORMAT>     jsonl or csv (default: csv for a .csv path, else jsonl)
+
+Keys (playback of files and test signals; V and Esc always work):
+  N / P                 Next / previous track
+  S                     Toggle shuffle
+  R                     Cycle repeat: off, all, one
+  Space                 Pause / resume
+  Left / Right          Seek 5 seconds back / forward
+  Down / Up             Seek 30 seconds back / forward
+  + / -                 Volume up / down by 10%
+  A / B                 Set the loop start / end; playback repeats A to B
+  C                     Clear the loop
+  V                     Switch the scene: tunnel, vectorscope
+  Esc                   Quit";
+
+// Görselleştirilen sesin nereden geldiği
+pub enum Input {
+    // `Options::tracks` içindeki dosyalar, dizinler ve listeler
+    Files,
+    // Aygıt adı ya da "default"
+    Capture(String),
+    // Ham PCM dosyası, adlandırılmış boru ya da standart girdi
+    Pcm(PcmConfig),
+    Signal(Signal),
+}
+
+pub struct Options {
+    pub input: Input,
+    pub tracks: Vec<PathBuf>,
+    // Yakalama için ses ana makinesi (alsa, jack, ...); None varsayılan
+    pub host: Option<String>,
+    // Test sinyalinin süresi, saniye; None sonsuzdur
+    pub duration: Option<f64>,
+    pub width: u32,
+    pub height: u32,
+    pub fullscreen: bool,
+    pub scene: Scene,
+    pub fft_size: usize,
+    pub hop_size: Option<usize>,
+    pub window: WindowFunction,
+    pub seed: Option<u64>,
+    pub volume: f32,
+    pub latency_ms: i32,
+    pub bands: Vec<Band>,
+    pub bindings: BandBindings,
+    pub shaping: Vec<FeatureShaping>,
+    pub spectrum: SpectrumLayout,
+    pub onset_sensitivity: f32,
+    pub audio_output: bool,
+    pub speed: f32,
+    // None varsayılan dizindir
+    pub cache_dir: Option<PathBuf>,
+    pub use_cache: bool,
+    pub shuffle: bool,
+    pub repeat: RepeatMode,
+    // Yalnızca `analyze` için; None standart çıktıdır
+    pub out: Option<PathBuf>,
+    pub format: Option<ExportFormat>,
+}
+
+impl Options {
+    pub fn analyzer_config(&self) -> AnalyzerConfig {
+        AnalyzerConfig {
+            fft_size: self.fft_size,
+            hop_size: self.hop_size.unwrap_or(self.fft_size / 2),
+            window: self.window,
+            latency_ms: self.latency_ms,
+            bands: self.bands.clone(),
+            shaping: self.shaping.clone(),
+            spectrum: self.spectrum,
+            onset_sensitivity: self.onset_sensitivity,
+        }
+    }
+
+    pub fn output_mode(&self) -> OutputMode {
+        if self.audio_output {
+            OutputMode::Device
+        } else {
+            OutputMode::Null { speed: self.speed }
+        }
+    }
+
+    pub fn cache_dir(&self) -> Option<PathBuf> {
+        if !self.use_cache {
+            return None;
+        }
+        self.cache_dir.clone().or_else(default_cache_dir)
+    }
+
+    // `analyze` sonlu bir sinyal ister
+    pub fn analyze_duration(&self) -> f64 {
+        self.duration.unwrap_or(10.0)
+    }
+
+    pub fn export_format(&self) -> ExportFormat {
+        self.format.unwrap_or_else(|| match &self.out {
+            Some(path) => ExportFormat::from_path(path),
+            None => ExportFormat::JsonLines,
+        })
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            input: Input::Files,
+            tracks: Vec::new(),
+            host: None,
+            duration: None,
+            width: 800,
+            height: 600,
+            fullscreen: false,
+            scene: Scene::Tunnel,
+            fft_size: 2048,
+            hop_size: None,
+            window: WindowFunction::Hann,
+            seed: None,
+            volume: 1.0,
+            latency_ms: 0,
+            bands: default_bands(),
+            bindings: BandBindings::resolve(&default_bands(), &[])
+                .expect("built-in bands cover the default bindings"),
+            shaping: vec![FeatureShaping::default(); default_bands().len()],
+            spectrum: SpectrumLayout::default(),
+            onset_sensitivity: 1.5,
+            audio_output: true,
+            speed: 1.0,
+            cache_dir: None,
+            use_cache: true,
+            shuffle: false,
+            repeat: RepeatMode::Off,
+            out: None,
+            format: None,
+        }
+    }
+}
+
+pub enum Command {
+    Run(Options),
+    // Pencere açmadan özellik zaman çizelgesi yazar
+    Analyze(Options),
+    // Ana makineler ve giriş aygıtları; isteğe bağlı tek bir ana makine için
+    ListDevices(Option<String>),
+    Help,
+}
+
+pub fn parse_args<I>(args: I) -> Result<Command, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut options = Options::default();
+    let mut args = args.into_iter().peekable();
+    let analyze = args.next_if(|arg| arg == "analyze").is_some();
+    let mut only_positional = false;
+    let mut bindings = Vec::new();
+    let mut shaping_overrides = Vec::new();
+    let mut list_devices = false;
+    // `--pcm` ile birlikte ya da ondan önce gelebilir
+    let mut pcm = PcmConfig::default();
+    let mut pcm_options = false;
+    let mut spectrum_range = false;
+
+    while let Some(arg) = args.next() {
+        if only_positional || !arg.starts_with('-') || arg == "-" {
+            options.tracks.push(PathBuf::from(arg));
+            continue;
+        }
+
+        // "--isim=deger" ve "--isim deger" biçimlerinin ikisi de kabul edilir
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) => (name.to_string(), Some(value.to_string())),
+            None => (arg.clone(), None),
+        };
+        let mut value = || -> Result<String, String> {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("missing value for '{}'", name))
+        };
+
+        match name.as_str() {
+            "--" => only_positional = true,
+            "-h" | "--help" => return Ok(Command::Help),
+            "--width" => options.width = parse_dimension(&name, &value()?)?,
+            "--height" => options.height = parse_dimension(&name, &value()?)?,
+            "--fullscreen" => options.fullscreen = flag(&name, &inline_value)?,
+            "--scene" => options.scene = value()?.parse()?,
+            "--fft-size" => options.fft_size = parse_fft_size(&value()?)?,
+            "--hop-size" => options.hop_size = Some(parse_dimension(&name, &value()?)? as usize),
+            "--window" => options.window = value()?.parse()?,
+            "--seed" => options.seed = Some(parse_number(&name, &value()?)?),
+            "--volume" => options.volume = parse_volume(&value()?)?,
+            "--band" => add_band(&mut options.bands, value()?.parse()?)?,
+            "--bind" => bindings.push(parse_binding(&value()?)?),
+            "--spectrum" => options.spectrum.scale = value()?.parse::<SpectrumScale>()?,
+            "--spectrum-range" => {
+                let (min_hz, max_hz) = parse_range(&name, &value()?)?;
+                options.spectrum.min_hz = min_hz;
+                options.spectrum.max_hz = max_hz;
+                spectrum_range = true;
+            }
+            "--smooth" => {
+                let (band, spec) = split_band_value(&name, &value()?)?;
+                let (attack_ms, release_ms) = parse_attack_release(&spec)?;
+                shaping_overrides.push((band, ShapingOverride::Smooth(attack_ms, release_ms)));
+            }
+            "--agc" => {
+                let (band, spec) = split_band_value(&name, &value()?)?;
+                let seconds: f32 = parse_number(&name, &spec)?;
+                if !(seconds >= 0.0 && seconds.is_finite()) {
+                    return Err("'--agc' window must be zero or positive".to_string());
+                }
+                shaping_overrides.push((band, ShapingOverride::Agc(seconds)));
+            }
+            "--onset-sensitivity" => {
+                options.onset_sensitivity = parse_number(&name, &value()?)?;
+                if !(options.onset_sensitivity >= 0.0 && options.onset_sensitivity.is_finite()) {
+                    return Err("'--onset-sensitivity' must be zero or positive".to_string());
+                }
+            }
+            "--latency" => options.latency_ms = parse_number(&name, &value()?)?,
+            "--no-audio-output" => options.audio_output = !flag(&name, &inline_value)?,
+            "--speed" => options.speed = parse_speed(&value()?)?,
+            "--cache-dir" => options.cache_dir = Some(PathBuf::from(value()?)),
+            "--no-cache" => options.use_cache = !flag(&name, &inline_value)?,
+            "--capture" => options.input = Input::Capture(value()?),
+            "--host" => options.host = Some(value()?),
+            "--pcm" => {
+                let path = value()?;
+                pcm.path = (path != "-").then(|| PathBuf::from(path));
+                options.input = Input::Pcm(PcmConfig::default());
+            }
+            "--pcm-format" => {
+                pcm.format = value()?.parse()?;
+                pcm_options = true;
+            }
+            "--pcm-rate" => {
+                pcm.sample_rate = parse_dimension(&name, &value()?)?;
+                pcm_options = true;
+            }
+            "--pcm-channels" => {
+                pcm.channels = parse_channels(&value()?)?;
+                pcm_options = true;
+            }
+            "--signal" => options.input = Input::Signal(value()?.parse()?),
+            "--duration" => options.duration = Some(parse_duration(&value()?)?),
+            "--list-devices" => list_devices = flag(&name, &inline_value)?,
+            "--shuffle" => options.shuffle = flag(&name, &inline_value)?,
+            "--repeat" => options.repeat = value()?.parse()?,
+            "--out" => {
+                let path = value()?;
+                options.out = (path != "-").then(|| PathBuf::from(path));
+            }
+            "--format" => options.format = Some(value()?.parse()?),
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+
+    if list_devices {
+        return Ok(Command::ListDevices(options.host));
+    }
+
+    if !analyze && (options.out.is_some() || options.format.is_some()) {
+        return Err("'--out' and '--format' can only be used with 'analyze'".to_string());
+    }
+
+    if options.speed != 1.0 && options.audio_output {
+        return Err("'--speed' can only be used with '--no-audio-output'".to_string());
+    }
+
+    if let Some(hop_size) = options.hop_size.filter(|&hop| hop > options.fft_size) {
+        return Err(format!(
+            "'--hop-size' must not exceed the FFT size ({}), got {}",
+            options.fft_size, hop_size
+        ));
+    }
+    let min_hop = options.fft_size / MIN_HOP_DIVISOR;
+    if let Some(hop_size) = options.hop_size.filter(|&hop| hop < min_hop) {
+        return Err(format!(
+            "'--hop-size' must be at least 1/{} of the FFT size ({}), got {}",
+            MIN_HOP_DIVISOR, options.fft_size, hop_size
+        ));
+    }
+
+    match &mut options.input {
+        Input::Pcm(config) => *config = pcm,
+        _ if pcm_options => {
+            return Err(
+                "'--pcm-format', '--pcm-rate' and '--pcm-channels' can only be used with '--pcm'"
+                    .to_string(),
+            );
+        }
+        _ => {}
+    }
+
+    // Dosya ve yakalama aygıtlarının hızı ancak açılınca belli olur; aralık o zaman
+    // yaygın en yüksek hızla denetlenir, analiz gerçek hızda aralığı kendisi kırpar
+    if spectrum_range {
+        let sample_rate = match &options.input {
+            Input::Pcm(config) => config.sample_rate,
+            Input::Signal(_) => SIGNAL_RATE,
+            Input::Files | Input::Capture(_) => REFERENCE_SAMPLE_RATE,
+        };
+        options.spectrum.check(sample_rate, options.fft_size)?;
+    }
+
+    options.bindings = BandBindings::resolve(&options.bands, &bindings)?;
+    options.shaping = resolve_shaping(&options.bands, &shaping_overrides)?;
+
+    if options.duration.is_some() && !matches!(options.input, Input::Signal(_)) {
+        return Err("'--duration' can only be used with '--signal'".to_string());
+    }
+
+    match options.input {
+        Input::Files if options.tracks.is_empty() => {
+            return Err("no audio file given".to_string());
+        }
+        Input::Files | Input::Pcm(_) | Input::Signal(_) if options.host.is_some() => {
+            return Err(
+                "'--host' can only be used with '--capture' or '--list-devices'".to_string(),
+            );
+        }
+        Input::Capture(_) | Input::Pcm(_) if analyze => {
+            return Err("'analyze' reads audio files and test signals only".to_string());
+        }
+        Input::Capture(_) | Input::Pcm(_) | Input::Signal(_) if !options.tracks.is_empty() => {
+            return Err(
+                "'--capture', '--pcm' and '--signal' cannot be combined with audio files"
+                    .to_string(),
+            );
+        }
+        _ => {}
+    }
+
+    if analyze {
+        if options.tracks.len() > 1 {
+            return Err("'analyze' takes exactly one audio file".to_string());
+        }
+        return Ok(Command::Analyze(options));
+    }
+
+    Ok(Command::Run(options))
+}
+
+fn flag(name: &str, inline_value: &Option<String>) -> Result<bool, String> {
+    match inline_value {
+        Some(_) => Err(format!("'{}' does not take a value", name)),
+        None => Ok(true),
+    }
+}
+
+fn add_band(bands: &mut Vec<Band>, band: Band) -> Result<(), String> {
+    if let Some(existing) = bands.iter_mut().find(|existing| existing.name == band.name) {
+        *existing = band;
+    } else if bands.len() < MAX_BANDS {
+        bands.push(band);
+    } else {
+        return Err(format!("at most {} bands are supported", MAX_BANDS));
+    }
+    Ok(())
+}
+
+enum ShapingOverride {
+    Smooth(f32, f32),
+    Agc(f32),
+}
+
+fn split_band_value(name: &str, value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(band, spec)| (band.to_string(), spec.to_string()))
+        .ok_or_else(|| {
+            format!(
+                "invalid value '{}' for '{}', expected BAND=...",
+                value, name
+            )
+        })
+}
+
+// Bantlar ancak tüm argümanlar okunduktan sonra kesinleştiği için en sonda uygulanır
+fn resolve_shaping(
+    bands: &[Band],
+    overrides: &[(String, ShapingOverride)],
+) -> Result<Vec<FeatureShaping>, String> {
+    let mut shaping = vec![FeatureShaping::default(); bands.len()];
+    for (target, change) in overrides {
+        let mut matched = false;
+        for (band, shaping) in bands.iter().zip(shaping.iter_mut()) {
+            if target != "all" && band.name != *target {
+                continue;
+            }
+            matched = true;
+            match *change {
+                ShapingOverride::Smooth(attack_ms, release_ms) => {
+                    shaping.attack_ms = attack_ms;
+                    shaping.release_ms = release_ms;
+                }
+                ShapingOverride::Agc(seconds) => shaping.agc_seconds = seconds,
+            }
+        }
+        if !matched {
+            return Err(format!("no band named '{}'", target));
+        }
+    }
+    Ok(shaping)
+}
+
+fn parse_binding(value: &str) -> Result<(VisualParam, String), String> {
+    let (param, band) = value
+        .split_once('=')
+        .ok_or_else(|| format!("invalid binding '{}', expected PARAM=BAND", value))?;
+    Ok((param.parse()?, band.to_string()))
+}
+
+fn parse_range(name: &str, value: &str) -> Result<(f32, f32), String> {
+    let invalid = || {
+        format!(
+            "invalid value '{}' for '{}', expected MIN-MAX in Hz",
+            value, name
+        )
+    };
+    let (min, max) = value.split_once('-').ok_or_else(invalid)?;
+    let min_hz: f32 = min.parse().map_err(|_| invalid())?;
+    let max_hz: f32 = max.parse().map_err(|_| invalid())?;
+    if !(min_hz > 0.0 && max_hz > min_hz && max_hz.is_finite()) {
+        return Err(invalid());
+    }
+    Ok((min_hz, max_hz))
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{}' for '{}'", value, name))
+}
+
+fn parse_dimension(name: &str, value: &str) -> Result<u32, String> {
+    match parse_number(name, value)? {
+        0 => Err(format!("'{}' must be greater than zero", name)),
+        pixels => Ok(pixels),
+    }
+}
+
+fn parse_fft_size(value: &str) -> Result<usize, String> {
+    let size: usize = parse_number("--fft-size", value)?;
+    if !size.is_power_of_two() || !(512..=16384).contains(&size) {
+        return Err(format!(
+            "'--fft-size' must be a power of two between 512 and 16384, got {}",
+            size
+        ));
+    }
+    Ok(size)
+}
+
+fn parse_duration(value: &str) -> Result<f64, String> {
+    let seconds: f64 = parse_number("--duration", value)?;
+    if !seconds.is_finite() || seconds <= 0.0 {
+        return Err(format!(
+            "'--duration' must be greater than zero, got {}",
+            value
+        ));
+    }
+    Ok(seconds)
+}
+
+fn parse_channels(value: &str) -> Result<u16, String> {
+    let channels: u16 = parse_number("--pcm-channels", value)?;
+    if !(1..=32).contains(&channels) {
+        return Err(format!(
+            "'--pcm-channels' must be between 1 and 32, got {}",
+            value
+        ));
+    }
+    Ok(channels)
+}
+
+fn parse_volume(value: &str) -> Result<f32, String> {
+    let volume: f32 = parse_number("--volume", value)?;
+    if !volume.is_finite() || volume < 0.0 {
+        return Err(format!(
+            "'--volume' must be zero or positive, got {}",
+            value
+        ));
+    }
+    Ok(volume)
+}
+
+fn parse_speed(value: &str) -> Result<f32, String> {
+    let speed: f32 = parse_number("--speed", value)?;
+    if !speed.is_finite() || speed <= 0.0 {
+        return Err(format!(
+            "'--speed' must be greater than zero, got {}",
+            value
+        ));
+    }
+    Ok(speed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn run(args: &[&str]) -> Options {
+        match parse(args) {
+            Ok(Command::Run(options)) => options,
+            Ok(_) => panic!("{:?} did not parse as a run", args),
+            Err(err) => panic!("{:?} was rejected: {}", args, err),
+        }
+    }
+
+    fn rejected(args: &[&str]) -> String {
+        match parse(args) {
+            Ok(_) => panic!("{:?} was accepted", args),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn defaults_and_both_value_forms() {
+        let options = run(&["a.mp3", "b.flac"]);
+        assert_eq!(
+            options.tracks,
+            vec![PathBuf::from("a.mp3"), PathBuf::from("b.flac")]
+        );
+        assert_eq!(options.analyzer_config().hop_size, 1024);
+
+        let options = run(&[
+            "--fft-size",
+            "4096",
+            "--hop-size=512",
+            "--width=1024",
+            "a.mp3",
+        ]);
+        assert_eq!((options.fft_size, options.hop_size), (4096, Some(512)));
+        assert_eq!(options.width, 1024);
+
+        // "--" sonrası tire ile başlayan adlar da dosyadır
+        let options = run(&["--", "--weird.mp3"]);
+        assert_eq!(options.tracks, vec![PathBuf::from("--weird.mp3")]);
+    }
+
+    #[test]
+    fn fft_size_must_be_a_power_of_two_in_range() {
+        for size in ["512", "2048", "16384"] {
+            assert_eq!(
+                run(&["--fft-size", size, "a.mp3"]).fft_size,
+                size.parse().unwrap()
+            );
+        }
+        for size in ["256", "32768", "1000", "0"] {
+            let err = rejected(&["--fft-size", size, "a.mp3"]);
+            assert!(
+                err.contains("power of two between 512 and 16384"),
+                "{}",
+                err
+            );
+        }
+        assert_eq!(
+            rejected(&["--fft-size", "big", "a.mp3"]),
+            "invalid value 'big' for '--fft-size'"
+        );
+    }
+
+    #[test]
+    fn hop_size_stays_within_the_fft_size() {
+        assert_eq!(run(&["--hop-size", "2048", "a.mp3"]).hop_size, Some(2048));
+        assert_eq!(run(&["--hop-size", "128", "a.mp3"]).hop_size, Some(128));
+        assert_eq!(
+            rejected(&["--hop-size", "4096", "a.mp3"]),
+            "'--hop-size' must not exceed the FFT size (2048), got 4096"
+        );
+        // Sınır `--fft-size` ile birlikte, sıradan bağımsız denetlenir
+        assert!(run(&["--hop-size", "4096", "--fft-size", "8192", "a.mp3"])
+            .hop_size
+            .is_some());
+        assert_eq!(
+            rejected(&["--hop-size", "64", "a.mp3"]),
+            "'--hop-size' must be at least 1/16 of the FFT size (2048), got 64"
+        );
+        assert_eq!(
+            rejected(&["--hop-size", "0", "a.mp3"]),
+            "'--hop-size' must be greater than zero"
+        );
+    }
+
+    #[test]
+    fn sources_are_exclusive_with_files() {
+        assert!(matches!(run(&["--signal", "sine"]).input, Input::Signal(_)));
+        assert!(matches!(
+            run(&["--capture", "mic"]).input,
+            Input::Capture(_)
+        ));
+        for source in [["--capture", "mic"], ["--pcm", "-"], ["--signal", "sine"]] {
+            let err = rejected(&[source[0], source[1], "a.mp3"]);
+            assert!(
+                err.contains("cannot be combined with audio files"),
+                "{}",
+                err
+            );
+        }
+        assert_eq!(
+            rejected(&["analyze", "--capture", "mic"]),
+            "'analyze' reads audio files and test signals only"
+        );
+        assert_eq!(
+            rejected(&["--host", "jack", "a.mp3"]),
+            "'--host' can only be used with '--capture' or '--list-devices'"
+        );
+        assert_eq!(
+            rejected(&["--pcm-rate", "44100", "a.mp3"]),
+            "'--pcm-format', '--pcm-rate' and '--pcm-channels' can only be used with '--pcm'"
+        );
+    }
+
+    #[test]
+    fn missing_values_and_unknown_flags_are_rejected() {
+        assert_eq!(
+            rejected(&["a.mp3", "--fft-size"]),
+            "missing value for '--fft-size'"
+        );
+        assert_eq!(rejected(&["--capture"]), "missing value for '--capture'");
+        assert_eq!(rejected(&[]), "no audio file given");
+        assert_eq!(
+            rejected(&["--frobnicate", "a.mp3"]),
+            "unknown option '--frobnicate'"
+        );
+        assert_eq!(
+            rejected(&["--fullscreen=yes", "a.mp3"]),
+            "'--fullscreen' does not take a value"
+        );
+        assert_eq!(
+            rejected(&["--out", "x.csv", "a.mp3"]),
+            "'--out' and '--format' can only be used with 'analyze'"
+        );
+    }
+
+    #[test]
+    fn commands_are_recognized() {
+        assert!(matches!(
+            parse(&["--help", "--frobnicate"]),
+            Ok(Command::Help)
+        ));
+        assert!(matches!(
+            parse(&["--list-devices"]),
+            Ok(Command::ListDevices(None))
+        ));
+        match parse(&["analyze", "a.mp3", "--out", "x.csv"]) {
+            Ok(Command::Analyze(options)) => {
+                assert_eq!(options.export_format(), ExportFormat::Csv)
+            }
+            _ => panic!("analyze was not recognized"),
+        }
+        assert_eq!(
+            rejected(&["analyze", "a.mp3", "b.mp3"]),
+            "'analyze' takes exactly one audio file"
+        );
+    }
+}
@@ -0,0 +1,390 @@
+use rodio::source::SeekError;
+use rodio::Source;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use symphonia::core::audio::{SampleBuffer, SignalSpec};
+use symphonia::core::codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error;
+use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::{MetadataOptions, MetadataRevision, StandardTagKey, StandardVisualKey};
+use symphonia::core::probe::Hint;
+use symphonia::core::units::{Time, TimeBase, TimeStamp};
+
+use crate::error::VisualizerError;
+
+// Art arda bu kadar paket çözülemezse dosyanın geri kalanı bozuk kabul edilir
+const MAX_CONSECUTIVE_ERRORS: usize = 64;
+
+#[derive(Clone, Debug, Default)]
+pub struct CoverArt {
+    // MIME türü, örn. "image/jpeg"
+    pub media_type: String,
+    pub data: Vec<u8>,
+}
+
+// Kap ve etiketlerden okunabilen her şey; hiçbiri zorunlu değildir
+#[derive(Clone, Debug, Default)]
+pub struct TrackInfo {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub duration: Option<Duration>,
+    pub cover_art: Option<CoverArt>,
+}
+
+impl TrackInfo {
+    // "Sanatçı - Başlık"; etiket yoksa dosya adı
+    pub fn label(&self, file_path: &Path) -> String {
+        match (&self.artist, &self.title) {
+            (Some(artist), Some(title)) => format!("{} - {}", artist, title),
+            (None, Some(title)) => title.clone(),
+            _ => file_path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| file_path.display().to_string()),
+        }
+    }
+
+    // Tek satırlık özet: etiket, albüm, süre ve kapak görseli
+    pub fn describe(&self, file_path: &Path) -> String {
+        let mut line = self.label(file_path);
+        if let Some(album) = &self.album {
+            line.push_str(&format!(" [{}]", album));
+        }
+        if let Some(duration) = self.duration {
+            line.push_str(&format!(" ({})", format_time(duration.as_secs_f64())));
+        }
+        if let Some(cover) = &self.cover_art {
+            line.push_str(&format!(
+                ", cover art {} ({} KiB)",
+                cover.media_type,
+                cover.data.len().div_ceil(1024)
+            ));
+        }
+        line
+    }
+
+    // Sonraki revizyonlar öncekilerin eksiklerini tamamlar, var olanı ezmez
+    fn merge(&mut self, revision: &MetadataRevision) {
+        for tag in revision.tags() {
+            let field = match tag.std_key {
+                Some(StandardTagKey::TrackTitle) => &mut self.title,
+                Some(StandardTagKey::Artist) => &mut self.artist,
+                Some(StandardTagKey::AlbumArtist) if self.artist.is_none() => &mut self.artist,
+                Some(StandardTagKey::Album) => &mut self.album,
+                _ => continue,
+            };
+            // RIFF INFO gibi kaplar değerleri NUL ile doldurabilir
+            let value = tag.value.to_string();
+            let value = value.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+            if field.is_none() && !value.is_empty() {
+                *field = Some(value.to_string());
+            }
+        }
+
+        // Ön kapak varsa o, yoksa ilk görsel
+        let visuals = revision.visuals();
+        let front = visuals
+            .iter()
+            .find(|visual| visual.usage == Some(StandardVisualKey::FrontCover))
+            .or_else(|| visuals.first());
+        if let (None, Some(visual)) = (&self.cover_art, front) {
+            self.cover_art = Some(CoverArt {
+                media_type: visual.media_type.clone(),
+                data: visual.data.to_vec(),
+            });
+        }
+    }
+}
+
+// "dakika:saniye"
+pub fn format_time(seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+// Symphonia ile çözülen, rodio kaynağı olarak çalınabilen parça. Bozuk paketler
+// atlanır; yalnızca art arda çok sayıda hata olursa parça erken biter.
+pub struct TrackSource {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    buffer: Option<SampleBuffer<f32>>,
+    // `buffer` içindeki bir sonraki örnek
+    position: usize,
+    spec: SignalSpec,
+    time_base: Option<TimeBase>,
+    duration: Option<Duration>,
+    // Atlamadan sonra bu zaman damgasından önceki örnekler atılır
+    seek_target: Option<TimeStamp>,
+    file_path: PathBuf,
+    skipped: usize,
+}
+
+pub fn open_track(file_path: &Path) -> Result<(TrackSource, TrackInfo), VisualizerError> {
+    let decode_error = |err| VisualizerError::Decode(file_path.to_path_buf(), err);
+    let file = File::open(file_path)
+        .map_err(|err| VisualizerError::from_io(file_path.to_path_buf(), err))?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(extension) = file_path
+        .extension()
+        .and_then(|extension| extension.to_str())
+    {
+        hint.with_extension(extension);
+    }
+    let mut probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            stream,
+            &FormatOptions {
+                enable_gapless: true,
+                ..Default::default()
+            },
+            &MetadataOptions::default(),
+        )
+        .map_err(decode_error)?;
+
+    let track = probed
+        .format
+        .tracks()
+        .iter()
+        .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| decode_error(Error::Unsupported("no audio track")))?;
+    let track_id = track.id;
+    let params = track.codec_params.clone();
+    let decoder = symphonia::default::get_codecs()
+        .make(&params, &DecoderOptions::default())
+        .map_err(decode_error)?;
+
+    let mut info = TrackInfo {
+        duration: params.n_frames.zip(params.time_base).map(|(frames, base)| {
+            let time = base.calc_time(frames);
+            Duration::from_secs(time.seconds) + Duration::from_secs_f64(time.frac)
+        }),
+        ..Default::default()
+    };
+    // Kabın kendi etiketleri önce, dosya başındaki ID3 gibi ek etiketler sonra
+    if let Some(revision) = probed.format.metadata().skip_to_latest() {
+        info.merge(revision);
+    }
+    if let Some(revision) = probed
+        .metadata
+        .get()
+        .as_mut()
+        .and_then(|metadata| metadata.skip_to_latest().cloned())
+    {
+        info.merge(&revision);
+    }
+
+    let mut source = TrackSource {
+        format: probed.format,
+        decoder,
+        track_id,
+        buffer: None,
+        position: 0,
+        spec: SignalSpec::new(
+            params.sample_rate.unwrap_or(44100),
+            params.channels.unwrap_or_default(),
+        ),
+        time_base: params.time_base,
+        duration: info.duration,
+        seek_target: None,
+        file_path: file_path.to_path_buf(),
+        skipped: 0,
+    };
+    // Kanal sayısı ve örnekleme hızı ilk paketten kesinleşir
+    source.refill();
+    Ok((source, info))
+}
+
+impl TrackSource {
+    // Bir sonraki çözülebilen paketi tampona alır; parça bittiyse false
+    fn refill(&mut self) -> bool {
+        let mut errors = 0;
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                Err(Error::DecodeError(_)) if errors < MAX_CONSECUTIVE_ERRORS => {
+                    errors += 1;
+                    self.skipped += 1;
+                    continue;
+                }
+                // Dosya sonu, okuma hatası ya da parça listesinin değişmesi
+                Err(_) => return self.finish(),
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            match self.decoder.decode(&packet) {
+                Ok(decoded) => {
+                    let spec = *decoded.spec();
+                    let frames = decoded.capacity() as u64;
+                    let fits = self.buffer.as_ref().is_some_and(|buffer| {
+                        self.spec == spec
+                            && buffer.capacity() >= frames as usize * spec.channels.count()
+                    });
+                    if !fits {
+                        self.buffer = Some(SampleBuffer::new(frames, spec));
+                    }
+                    self.spec = spec;
+                    let buffer = self.buffer.as_mut().unwrap();
+                    buffer.copy_interleaved_ref(decoded);
+                    self.position = 0;
+
+                    // Kap yalnızca hedeften önceki bir pakete konumlanabilir
+                    if let (Some(target), Some(base)) = (self.seek_target, self.time_base) {
+                        if packet.ts() + packet.dur() <= target {
+                            continue;
+                        }
+                        let early = base.calc_time(target.saturating_sub(packet.ts()));
+                        let frames = ((early.seconds as f64 + early.frac) * spec.rate as f64)
+                            .round() as usize;
+                        self.position = (frames * spec.channels.count()).min(buffer.len());
+                        self.seek_target = None;
+                    }
+                    if self.remaining() > 0 {
+                        return true;
+                    }
+                }
+                Err(Error::DecodeError(_)) if errors < MAX_CONSECUTIVE_ERRORS => {
+                    errors += 1;
+                    self.skipped += 1;
+                }
+                Err(_) => return self.finish(),
+            }
+        }
+    }
+
+    fn finish(&mut self) -> bool {
+        if let Some(buffer) = &mut self.buffer {
+            buffer.clear();
+        }
+        self.position = 0;
+        if self.skipped > 0 {
+            eprintln!(
+                "warning: skipped {} corrupt packets in {}",
+                self.skipped,
+                self.file_path.display()
+            );
+            self.skipped = 0;
+        }
+        false
+    }
+
+    fn remaining(&self) -> usize {
+        self.buffer
+            .as_ref()
+            .map_or(0, |buffer| buffer.len() - self.position)
+    }
+}
+
+impl Iterator for TrackSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.remaining() == 0 {
+            return None;
+        }
+        let sample = self.buffer.as_ref()?.samples()[self.position];
+        self.position += 1;
+        // Tampon hemen doldurulur; boş tampon rodio'ya kaynağın bittiğini söyler
+        if self.remaining() == 0 {
+            self.refill();
+        }
+        Some(sample)
+    }
+}
+
+impl Source for TrackSource {
+    // Kanal sayısı ya da hız paketler arasında değişebileceği için her tampon bir çerçevedir;
+    // tampon yalnızca parça bitince boştur
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.remaining())
+    }
+
+    fn channels(&self) -> u16 {
+        self.spec.channels.count().max(1) as u16
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.spec.rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let seeked = self
+            .format
+            .seek(
+                SeekMode::Accurate,
+                SeekTo::Time {
+                    time: Time::from(pos),
+                    track_id: Some(self.track_id),
+                },
+            )
+            .map_err(|err| SeekError::Other(Box::new(err)))?;
+        self.decoder.reset();
+        self.seek_target = Some(seeked.required_ts);
+        if let Some(buffer) = &mut self.buffer {
+            buffer.clear();
+        }
+        self.position = 0;
+        self.refill();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use rodio::source::UniformSourceIterator;
+
+    // 16 bit stereo PCM WAV; her çerçevede sol ve sağ aynı rampadır
+    pub(crate) fn write_wav(path: &Path, sample_rate: u32, frames: usize) {
+        let data_len = (frames * 4) as u32;
+        let mut bytes = Vec::with_capacity(44 + data_len as usize);
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&(sample_rate * 4).to_le_bytes());
+        bytes.extend_from_slice(&4u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        for frame in 0..frames {
+            let sample = ((frame % 200) as i16 - 100) * 100;
+            bytes.extend_from_slice(&sample.to_le_bytes());
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+        std::fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn mixer_plays_every_packet() {
+        let path = std::env::temp_dir().join(format!("decoder-test-{}.wav", std::process::id()));
+        write_wav(&path, 44100, 44100);
+        let (source, _) = open_track(&path).unwrap();
+        // rodio'nun karıştırıcısı kaynakları bu yineleyiciyle sarar ve
+        // sıfır uzunluklu çerçevede kaynağı bitmiş sayar
+        let samples = UniformSourceIterator::<_, f32>::new(source, 2, 44100).count();
+        assert_eq!(samples, 88200);
+
+        let (mut source, _) = open_track(&path).unwrap();
+        source.try_seek(Duration::from_millis(500)).unwrap();
+        assert_ne!(source.current_frame_len(), Some(0));
+        let samples = UniformSourceIterator::<_, f32>::new(source, 2, 44100).count();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(samples, 44100);
+    }
+}
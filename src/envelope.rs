@@ -0,0 +1,251 @@
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+// Otomatik kazancın normalize ettiği yüzdelik aralığı
+const AGC_LOW_PERCENTILE: f32 = 0.05;
+const AGC_HIGH_PERCENTILE: f32 = 0.95;
+// Sessiz ya da düz bölümlerde gürültünün tam ölçeğe şişirilmemesi için en küçük aralık
+const AGC_MIN_RANGE: f32 = 0.05;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FeatureShaping {
+    pub attack_ms: f32,
+    pub release_ms: f32,
+    // Otomatik kazanç penceresi; 0 ise kapalı
+    pub agc_seconds: f32,
+}
+
+impl Default for FeatureShaping {
+    fn default() -> Self {
+        Self {
+            attack_ms: 10.0,
+            release_ms: 150.0,
+            agc_seconds: 10.0,
+        }
+    }
+}
+
+// "ATAK:BIRAKMA" milisaniye, örn. "5:300"
+pub fn parse_attack_release(spec: &str) -> Result<(f32, f32), String> {
+    let invalid = || {
+        format!(
+            "invalid smoothing '{}', expected ATTACK:RELEASE in ms",
+            spec
+        )
+    };
+    let (attack, release) = spec.split_once(':').ok_or_else(invalid)?;
+    let attack_ms = f32::from_str(attack).map_err(|_| invalid())?;
+    let release_ms = f32::from_str(release).map_err(|_| invalid())?;
+    if !(attack_ms >= 0.0 && release_ms >= 0.0 && attack_ms.is_finite() && release_ms.is_finite()) {
+        return Err(invalid());
+    }
+    Ok((attack_ms, release_ms))
+}
+
+fn coefficient(time_ms: f32, hop_seconds: f32) -> f32 {
+    if time_ms <= 0.0 {
+        0.0
+    } else {
+        (-hop_seconds / (time_ms / 1000.0)).exp()
+    }
+}
+
+// Yükselişte atak, düşüşte bırakma süresiyle tek kutuplu takipçi
+pub struct EnvelopeFollower {
+    value: f32,
+    attack: f32,
+    release: f32,
+}
+
+impl EnvelopeFollower {
+    pub fn new(attack_ms: f32, release_ms: f32, hop_seconds: f32) -> Self {
+        Self {
+            value: 0.0,
+            attack: coefficient(attack_ms, hop_seconds),
+            release: coefficient(release_ms, hop_seconds),
+        }
+    }
+
+    pub fn process(&mut self, input: f32) -> f32 {
+        let coefficient = if input > self.value {
+            self.attack
+        } else {
+            self.release
+        };
+        self.value = input + coefficient * (self.value - input);
+        self.value
+    }
+}
+
+// Parçanın yakın geçmişine göre 0..1'e normalize eden kayan pencere
+pub struct AutoGain {
+    history: VecDeque<f32>,
+    capacity: usize,
+    // `history` ile aynı değerler, sıralı; her kare yalnızca bir ekleme ve bir silme
+    sorted: Vec<f32>,
+}
+
+impl AutoGain {
+    pub fn new(window_seconds: f32, hop_seconds: f32) -> Self {
+        let capacity = ((window_seconds / hop_seconds).round() as usize).max(2);
+        Self {
+            history: VecDeque::with_capacity(capacity),
+            capacity,
+            sorted: Vec::with_capacity(capacity + 1),
+        }
+    }
+
+    pub fn process(&mut self, input: f32) -> f32 {
+        self.history.push_back(input);
+        let at = self
+            .sorted
+            .partition_point(|value| value.total_cmp(&input).is_lt());
+        self.sorted.insert(at, input);
+        if self.history.len() > self.capacity {
+            if let Some(oldest) = self.history.pop_front() {
+                if let Ok(at) = self
+                    .sorted
+                    .binary_search_by(|value| value.total_cmp(&oldest))
+                {
+                    self.sorted.remove(at);
+                }
+            }
+        }
+
+        let percentile =
+            |p: f32| self.sorted[((self.sorted.len() - 1) as f32 * p).round() as usize];
+        let low = percentile(AGC_LOW_PERCENTILE);
+        let high = percentile(AGC_HIGH_PERCENTILE);
+
+        ((input - low) / (high - low).max(AGC_MIN_RANGE)).clamp(0.0, 1.0)
+    }
+}
+
+// Bir özelliğin ham değerinden görsellere giden değere: önce kazanç, sonra yumuşatma
+pub struct FeatureShaper {
+    gain: Option<AutoGain>,
+    follower: EnvelopeFollower,
+}
+
+impl FeatureShaper {
+    pub fn new(shaping: &FeatureShaping, hop_seconds: f32) -> Self {
+        Self {
+            gain: (shaping.agc_seconds > 0.0)
+                .then(|| AutoGain::new(shaping.agc_seconds, hop_seconds)),
+            follower: EnvelopeFollower::new(shaping.attack_ms, shaping.release_ms, hop_seconds),
+        }
+    }
+
+    pub fn process(&mut self, input: f32) -> f32 {
+        let normalized = match &mut self.gain {
+            Some(gain) => gain.process(input),
+            None => input,
+        };
+        self.follower.process(normalized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOP_SECONDS: f32 = 0.01;
+
+    // Aynı pencerenin her karede baştan sıralanmasıyla elde edilen sonuç
+    fn brute_force(window: &[f32], input: f32) -> f32 {
+        let mut sorted = window.to_vec();
+        sorted.sort_by(f32::total_cmp);
+        let percentile = |p: f32| sorted[((sorted.len() - 1) as f32 * p).round() as usize];
+        let low = percentile(AGC_LOW_PERCENTILE);
+        let high = percentile(AGC_HIGH_PERCENTILE);
+        ((input - low) / (high - low).max(AGC_MIN_RANGE)).clamp(0.0, 1.0)
+    }
+
+    #[test]
+    fn percentiles_match_a_sorted_copy_after_wrapping() {
+        // 50 karelik pencere, birkaç kez dolup taşar
+        let mut gain = AutoGain::new(0.5, HOP_SECONDS);
+        let inputs: Vec<f32> = (0..400)
+            .map(|i| ((i * 37 % 101) as f32 / 100.0) * (1.0 + (i / 100) as f32))
+            .collect();
+        for (i, &input) in inputs.iter().enumerate() {
+            let window = &inputs[(i + 1).saturating_sub(50)..=i];
+            assert_eq!(
+                gain.process(input),
+                brute_force(window, input),
+                "frame {}",
+                i
+            );
+        }
+        assert_eq!(gain.history.len(), 50);
+    }
+
+    #[test]
+    fn duplicates_leave_the_window_one_at_a_time() {
+        let mut gain = AutoGain::new(0.1, HOP_SECONDS);
+        let inputs = [0.5, 0.5, 0.2, 0.5, 0.2, 0.2, 0.9, 0.5, 0.5, 0.5, 0.5, 0.1];
+        for _ in 0..5 {
+            for &input in &inputs {
+                gain.process(input);
+                let mut expected: Vec<f32> = gain.history.iter().copied().collect();
+                expected.sort_by(f32::total_cmp);
+                assert_eq!(gain.sorted, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn window_forgets_an_old_level() {
+        let mut gain = AutoGain::new(0.5, HOP_SECONDS);
+        for _ in 0..50 {
+            gain.process(1.0);
+        }
+        // Yüksek bölüm penceredeyken sessiz bölüm en alta oturur
+        assert_eq!(gain.process(0.1), 0.0);
+        for _ in 0..60 {
+            gain.process(0.1);
+        }
+        // Pencere tamamen döndükten sonra eski seviye hiçbir etki bırakmaz
+        assert!(gain.sorted.iter().all(|&value| value == 0.1));
+        assert_eq!(gain.process(0.2), 1.0);
+    }
+
+    #[test]
+    fn attack_is_faster_than_release() {
+        let mut follower = EnvelopeFollower::new(10.0, 150.0, HOP_SECONDS);
+        let rise = follower.process(1.0);
+        // 10 ms atak, 10 ms hop'ta tek karede yolun 1 - e^-1'ini alır
+        assert!((rise - (1.0 - (-1.0f32).exp())).abs() < 1e-6);
+
+        for _ in 0..100 {
+            follower.process(1.0);
+        }
+        let fall = 1.0 - follower.process(0.0);
+        assert!((fall - (1.0 - (-1.0f32 / 15.0).exp())).abs() < 1e-4);
+        assert!(rise > 5.0 * fall);
+        // Düşüş sürerken değer girişin altına inmez
+        let mut previous = 1.0 - fall;
+        for _ in 0..20 {
+            let value = follower.process(0.0);
+            assert!(value < previous && value > 0.0);
+            previous = value;
+        }
+    }
+
+    #[test]
+    fn zero_times_follow_the_input_immediately() {
+        let mut follower = EnvelopeFollower::new(0.0, 0.0, HOP_SECONDS);
+        assert_eq!(follower.process(0.7), 0.7);
+        assert_eq!(follower.process(0.2), 0.2);
+
+        let mut shaper = FeatureShaper::new(
+            &FeatureShaping {
+                attack_ms: 0.0,
+                release_ms: 0.0,
+                agc_seconds: 0.0,
+            },
+            HOP_SECONDS,
+        );
+        assert_eq!(shaper.process(0.3), 0.3);
+    }
+}
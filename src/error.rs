@@ -0,0 +1,83 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum VisualizerError {
+    FileNotFound(PathBuf),
+    FileOpen(PathBuf, io::Error),
+    // Dizinler ve çalma listeleri açıldıktan sonra hiç parça kalmadı
+    EmptyPlaylist,
+    Decode(PathBuf, symphonia::core::errors::Error),
+    NoOutputDevice(rodio::StreamError),
+    Capture(String),
+    GlInit(String),
+    ShaderCompile { stage: &'static str, log: String },
+    ShaderLink(String),
+    Write(PathBuf, io::Error),
+    // Arka plandaki analiz durduruldu; kullanıcıya gösterilmez
+    Cancelled,
+}
+
+impl fmt::Display for VisualizerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VisualizerError::FileNotFound(path) => {
+                write!(f, "audio file not found: {}", path.display())
+            }
+            VisualizerError::FileOpen(path, err) => {
+                write!(f, "cannot open {}: {}", path.display(), err)
+            }
+            VisualizerError::EmptyPlaylist => {
+                write!(f, "no audio files found in the given paths")
+            }
+            VisualizerError::Decode(path, err) => {
+                write!(f, "cannot decode {}: {}", path.display(), err)
+            }
+            VisualizerError::NoOutputDevice(err) => {
+                write!(
+                    f,
+                    "no audio output device available ({}); use --no-audio-output to run silently",
+                    err
+                )
+            }
+            VisualizerError::Capture(message) => {
+                write!(f, "audio capture failed: {}", message)
+            }
+            VisualizerError::GlInit(message) => {
+                write!(f, "OpenGL initialization failed: {}", message)
+            }
+            VisualizerError::ShaderCompile { stage, log } => {
+                write!(f, "{} shader failed to compile: {}", stage, log.trim_end())
+            }
+            VisualizerError::ShaderLink(log) => {
+                write!(f, "shader program failed to link: {}", log.trim_end())
+            }
+            VisualizerError::Write(path, err) => {
+                write!(f, "cannot write {}: {}", path.display(), err)
+            }
+            VisualizerError::Cancelled => write!(f, "analysis cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for VisualizerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VisualizerError::FileOpen(_, err) => Some(err),
+            VisualizerError::Decode(_, err) => Some(err),
+            VisualizerError::NoOutputDevice(err) => Some(err),
+            VisualizerError::Write(_, err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl VisualizerError {
+    pub fn from_io(path: PathBuf, err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::NotFound => VisualizerError::FileNotFound(path),
+            _ => VisualizerError::FileOpen(path, err),
+        }
+    }
+}
@@ -0,0 +1,506 @@
+use rodio::Source;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::analysis::{analyze_track, summarize_spectrum, AnalyzerConfig, SpectrumSummary};
+use crate::bands::Band;
+use crate::chroma::NOTE_NAMES;
+use crate::decoder::open_track;
+use crate::error::VisualizerError;
+use crate::features::AudioFeatures;
+use crate::onset::BeatEvent;
+use crate::signal::{Signal, SignalSource};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    // Her hop için bir JSON nesnesi, satır başına bir tane
+    JsonLines,
+    Csv,
+}
+
+impl ExportFormat {
+    // Uzantı ".csv" ise CSV, aksi halde JSON Lines
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("csv") => ExportFormat::Csv,
+            _ => ExportFormat::JsonLines,
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "jsonl" | "json-lines" | "ndjson" => Ok(ExportFormat::JsonLines),
+            "csv" => Ok(ExportFormat::Csv),
+            _ => Err(format!(
+                "unknown export format '{}', expected jsonl or csv",
+                s
+            )),
+        }
+    }
+}
+
+// Parçanın her hop'taki özelliklerini `out_path`'e (None ise standart çıktıya) yazar.
+// Yazılan kare sayısını döndürür.
+pub fn export_features(
+    file_path: &Path,
+    config: &AnalyzerConfig,
+    format: ExportFormat,
+    out_path: Option<&Path>,
+) -> Result<usize, VisualizerError> {
+    // Çıktı dosyası parça açılamazsa boş kalmasın diye önce çözücü açılır
+    let (source, info) = open_track(file_path)?;
+    eprintln!("analyzing {}", info.describe(file_path));
+    export_source(source, config, format, out_path)
+}
+
+// Test sinyali için `export_features`; sinyalin süresi sonlu olmalıdır
+pub fn export_signal(
+    signal: Signal,
+    duration: f64,
+    config: &AnalyzerConfig,
+    format: ExportFormat,
+    out_path: Option<&Path>,
+) -> Result<usize, VisualizerError> {
+    eprintln!("analyzing {} for {} s", signal, duration);
+    export_source(
+        SignalSource::new(signal, Some(duration)),
+        config,
+        format,
+        out_path,
+    )
+}
+
+fn export_source<S>(
+    source: S,
+    config: &AnalyzerConfig,
+    format: ExportFormat,
+    out_path: Option<&Path>,
+) -> Result<usize, VisualizerError>
+where
+    S: Source<Item = f32>,
+{
+    let out_name = out_path.map_or_else(|| PathBuf::from("<stdout>"), Path::to_path_buf);
+    let write_error = |err| VisualizerError::Write(out_name.clone(), err);
+
+    let mut out: Box<dyn Write> = match out_path {
+        Some(path) => Box::new(BufWriter::new(File::create(path).map_err(write_error)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    write_header(format, &config.bands, &mut out).map_err(write_error)?;
+
+    let mut frames = 0;
+    analyze_track(source, config, |features, extractor| {
+        let analyzer = extractor.spectrum();
+        let frame = Frame {
+            features,
+            onsets: extractor.new_beats(),
+            summary: summarize_spectrum(analyzer.spectrum(), analyzer.bin_hz()),
+        };
+        match format {
+            ExportFormat::JsonLines => frame.write_json(&config.bands, &mut out),
+            ExportFormat::Csv => frame.write_csv(&config.bands, &mut out),
+        }
+        .map_err(write_error)?;
+        frames += 1;
+        Ok(())
+    })?;
+
+    out.flush().map_err(write_error)?;
+    Ok(frames)
+}
+
+struct Frame<'a> {
+    features: &'a AudioFeatures,
+    // Bu hop'ta ilk kez görülen vuruşlar
+    onsets: &'a [BeatEvent],
+    summary: SpectrumSummary,
+}
+
+fn write_header(format: ExportFormat, bands: &[Band], out: &mut dyn Write) -> io::Result<()> {
+    if format != ExportFormat::Csv {
+        return Ok(());
+    }
+
+    write!(out, "time")?;
+    for band in bands {
+        write!(out, ",{}", csv_field(&band.name))?;
+    }
+    write!(
+        out,
+        ",centroid_hz,rolloff_hz,flatness,peak_hz,bpm,tempo_confidence,beat_phase,bar_phase,\
+         stereo_mid,stereo_side,stereo_width,stereo_balance"
+    )?;
+    for band in bands {
+        write!(out, ",{}", csv_field(&format!("pan_{}", band.name)))?;
+    }
+    write!(out, ",key,key_confidence")?;
+    for note in NOTE_NAMES {
+        write!(out, ",chroma_{}", note)?;
+    }
+    writeln!(out, ",onsets")
+}
+
+impl Frame<'_> {
+    fn write_json(&self, bands: &[Band], out: &mut dyn Write) -> io::Result<()> {
+        let features = self.features;
+        write!(out, "{{\"time\":{:.4},\"bands\":{{", features.timestamp)?;
+        for (index, (band, energy)) in bands.iter().zip(&features.bands).enumerate() {
+            let separator = if index > 0 { "," } else { "" };
+            write!(
+                out,
+                "{}{}:{:.4}",
+                separator,
+                json_string(&band.name),
+                energy
+            )?;
+        }
+        write!(
+            out,
+            "}},\"spectrum\":{{\"centroid_hz\":{:.1},\"rolloff_hz\":{:.1},\"flatness\":{:.4},\"peak_hz\":{:.1}}}",
+            self.summary.centroid_hz,
+            self.summary.rolloff_hz,
+            self.summary.flatness,
+            self.summary.peak_hz
+        )?;
+        let stereo = &features.stereo;
+        write!(
+            out,
+            ",\"stereo\":{{\"mid\":{:.4},\"side\":{:.4},\"width\":{:.4},\"balance\":{:.4},\"pan\":{{",
+            stereo.mid, stereo.side, stereo.width, stereo.balance
+        )?;
+        for (index, (band, pan)) in bands.iter().zip(&stereo.band_pan).enumerate() {
+            let separator = if index > 0 { "," } else { "" };
+            write!(out, "{}{}:{:.4}", separator, json_string(&band.name), pan)?;
+        }
+        write!(out, "}}}},\"chroma\":[")?;
+        for (index, value) in features.chroma.iter().enumerate() {
+            let separator = if index > 0 { "," } else { "" };
+            write!(out, "{}{:.4}", separator, value)?;
+        }
+        write!(
+            out,
+            "],\"key\":{{\"name\":{},\"confidence\":{:.4}}},\"onsets\":[",
+            json_string(&features.key.to_string()),
+            features.key.confidence
+        )?;
+        for (index, beat) in self.onsets.iter().enumerate() {
+            let separator = if index > 0 { "," } else { "" };
+            write!(
+                out,
+                "{}{{\"band\":{},\"time\":{:.4},\"strength\":{:.4}}}",
+                separator,
+                json_string(&bands[beat.band].name),
+                beat.time,
+                beat.strength
+            )?;
+        }
+        writeln!(
+            out,
+            "],\"bpm\":{:.2},\"tempo_confidence\":{:.4},\"beat_phase\":{:.4},\"bar_phase\":{:.4}}}",
+            features.tempo.bpm,
+            features.tempo.confidence,
+            features.tempo.beat_phase,
+            features.tempo.bar_phase
+        )
+    }
+
+    // Vuruşlar tek sütunda "bant:güç" çiftleri olarak, ';' ile ayrılır
+    fn write_csv(&self, bands: &[Band], out: &mut dyn Write) -> io::Result<()> {
+        let features = self.features;
+        write!(out, "{:.4}", features.timestamp)?;
+        for energy in &features.bands {
+            write!(out, ",{:.4}", energy)?;
+        }
+        write!(
+            out,
+            ",{:.1},{:.1},{:.4},{:.1},{:.2},{:.4},{:.4},{:.4},",
+            self.summary.centroid_hz,
+            self.summary.rolloff_hz,
+            self.summary.flatness,
+            self.summary.peak_hz,
+            features.tempo.bpm,
+            features.tempo.confidence,
+            features.tempo.beat_phase,
+            features.tempo.bar_phase
+        )?;
+        let stereo = &features.stereo;
+        write!(
+            out,
+            "{:.4},{:.4},{:.4},{:.4},",
+            stereo.mid, stereo.side, stereo.width, stereo.balance
+        )?;
+        for pan in &stereo.band_pan {
+            write!(out, "{:.4},", pan)?;
+        }
+        write!(out, "{},{:.4},", features.key, features.key.confidence)?;
+        for value in &features.chroma {
+            write!(out, "{:.4},", value)?;
+        }
+        let onsets: Vec<String> = self
+            .onsets
+            .iter()
+            .map(|beat| format!("{}:{:.4}", bands[beat.band].name, beat.strength))
+            .collect();
+        writeln!(out, "{}", csv_field(&onsets.join(";")))
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chroma::PITCH_CLASSES;
+    use crate::envelope::FeatureShaping;
+    use std::collections::BTreeMap;
+    use std::fs;
+
+    // Ters bölü, tırnak ve virgül içeren bir bant adı; Windows yolu gibi
+    const AWKWARD_NAME: &str = "C:\\mix \"live\", take 2";
+
+    #[derive(Debug, PartialEq)]
+    enum Json {
+        Number(f64),
+        String(String),
+        Array(Vec<Json>),
+        Object(BTreeMap<String, Json>),
+    }
+
+    impl Json {
+        fn get(&self, key: &str) -> &Json {
+            match self {
+                Json::Object(fields) => &fields[key],
+                _ => panic!("not an object"),
+            }
+        }
+    }
+
+    // Dışa aktarımın kullandığı JSON alt kümesi için küçük bir ayrıştırıcı
+    fn parse_json(text: &str) -> Json {
+        let mut chars = text.chars().peekable();
+        let value = parse_value(&mut chars);
+        assert_eq!(chars.next(), None, "trailing characters");
+        value
+    }
+
+    fn parse_value(chars: &mut std::iter::Peekable<std::str::Chars>) -> Json {
+        match chars.next() {
+            Some('"') => Json::String(parse_string(chars)),
+            Some('[') => {
+                let mut items = Vec::new();
+                if chars.peek() == Some(&']') {
+                    chars.next();
+                    return Json::Array(items);
+                }
+                loop {
+                    items.push(parse_value(chars));
+                    match chars.next() {
+                        Some(',') => {}
+                        Some(']') => return Json::Array(items),
+                        other => panic!("unexpected {:?} in array", other),
+                    }
+                }
+            }
+            Some('{') => {
+                let mut fields = BTreeMap::new();
+                if chars.peek() == Some(&'}') {
+                    chars.next();
+                    return Json::Object(fields);
+                }
+                loop {
+                    assert_eq!(chars.next(), Some('"'));
+                    let key = parse_string(chars);
+                    assert_eq!(chars.next(), Some(':'));
+                    assert!(fields.insert(key, parse_value(chars)).is_none());
+                    match chars.next() {
+                        Some(',') => {}
+                        Some('}') => return Json::Object(fields),
+                        other => panic!("unexpected {:?} in object", other),
+                    }
+                }
+            }
+            Some(first) => {
+                let mut number = first.to_string();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')) {
+                        break;
+                    }
+                    number.push(c);
+                    chars.next();
+                }
+                Json::Number(number.parse().unwrap())
+            }
+            None => panic!("unexpected end"),
+        }
+    }
+
+    fn parse_string(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+        let mut value = String::new();
+        loop {
+            match chars.next().expect("unterminated string") {
+                '"' => return value,
+                '\\' => match chars.next() {
+                    Some('"') => value.push('"'),
+                    Some('\\') => value.push('\\'),
+                    Some('u') => {
+                        let code: String = chars.by_ref().take(4).collect();
+                        let code = u32::from_str_radix(&code, 16).unwrap();
+                        value.push(char::from_u32(code).unwrap());
+                    }
+                    other => panic!("unexpected escape {:?}", other),
+                },
+                c => {
+                    assert!(c as u32 >= 0x20, "raw control character");
+                    value.push(c);
+                }
+            }
+        }
+    }
+
+    // Tırnaklı alanlardaki virgüller sütun ayırmaz
+    fn csv_columns(line: &str) -> Vec<String> {
+        let mut columns = vec![String::new()];
+        let mut quoted = false;
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if quoted && chars.peek() == Some(&'"') => {
+                    chars.next();
+                    columns.last_mut().unwrap().push('"');
+                }
+                '"' => quoted = !quoted,
+                ',' if !quoted => columns.push(String::new()),
+                c => columns.last_mut().unwrap().push(c),
+            }
+        }
+        assert!(!quoted, "unterminated quote");
+        columns
+    }
+
+    fn config() -> AnalyzerConfig {
+        AnalyzerConfig {
+            bands: vec![
+                Band::new("low", 20.0, 200.0),
+                Band::new(AWKWARD_NAME, 200.0, 2000.0),
+                Band::new("high", 2000.0, 20000.0),
+            ],
+            shaping: vec![FeatureShaping::default(); 3],
+            ..AnalyzerConfig::default()
+        }
+    }
+
+    // İmpuls dizisi, her bantta vuruş üretir
+    fn export(name: &str, format: ExportFormat, seconds: f64) -> (usize, String) {
+        let path = std::env::temp_dir().join(format!("export-{}-{}", name, std::process::id()));
+        let frames = export_signal(
+            Signal::Impulses(120.0),
+            seconds,
+            &config(),
+            format,
+            Some(&path),
+        )
+        .unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        (frames, text)
+    }
+
+    #[test]
+    fn csv_rows_match_the_header() {
+        let (frames, text) = export("csv", ExportFormat::Csv, 3.0);
+        let mut lines = text.lines();
+        let header = csv_columns(lines.next().unwrap());
+        // Zaman, bantlar, 12 sabit sütun, pan'lar, anahtar ve güveni, kroma, vuruşlar
+        assert_eq!(header.len(), 1 + 3 + 12 + 3 + 2 + PITCH_CLASSES + 1);
+        assert_eq!(header[2], AWKWARD_NAME);
+        assert_eq!(header[17], format!("pan_{}", AWKWARD_NAME));
+        assert_eq!(header[header.len() - 2], "chroma_B");
+
+        let rows: Vec<Vec<String>> = lines.map(csv_columns).collect();
+        assert_eq!(rows.len(), frames);
+        assert!(frames > 0);
+        for row in &rows {
+            assert_eq!(row.len(), header.len(), "{:?}", row);
+        }
+        assert!(rows
+            .iter()
+            .any(|row| row.last().unwrap().contains(AWKWARD_NAME)));
+    }
+
+    #[test]
+    fn json_lines_parse_and_keep_names_intact() {
+        let (frames, text) = export("json", ExportFormat::JsonLines, 3.0);
+        let lines: Vec<Json> = text.lines().map(parse_json).collect();
+        assert_eq!(lines.len(), frames);
+
+        let mut onsets = 0;
+        for line in &lines {
+            let Json::Object(bands) = line.get("bands") else {
+                panic!("bands is not an object");
+            };
+            assert!(bands.contains_key(AWKWARD_NAME));
+            let Json::Array(chroma) = line.get("chroma") else {
+                panic!("chroma is not an array");
+            };
+            assert_eq!(chroma.len(), PITCH_CLASSES);
+            let Json::Array(beats) = line.get("onsets") else {
+                panic!("onsets is not an array");
+            };
+            onsets += beats.len();
+        }
+        assert!(onsets > 0);
+    }
+
+    #[test]
+    fn paths_are_escaped_as_json_strings() {
+        let path = "C:\\Music\\\"Live\"\\01\ttrack.flac";
+        let escaped = json_string(path);
+        assert_eq!(
+            escaped,
+            "\"C:\\\\Music\\\\\\\"Live\\\"\\\\01\\u0009track.flac\""
+        );
+        assert_eq!(parse_json(&escaped), Json::String(path.to_string()));
+        assert_eq!(
+            parse_json(&json_string("/müzik/şarkı.mp3")),
+            Json::String("/müzik/şarkı.mp3".to_string())
+        );
+    }
+
+    #[test]
+    fn empty_timeline_writes_only_the_header() {
+        let (frames, text) = export("empty-json", ExportFormat::JsonLines, 0.0);
+        assert_eq!((frames, text.as_str()), (0, ""));
+
+        let (frames, text) = export("empty-csv", ExportFormat::Csv, 0.0);
+        assert_eq!(frames, 0);
+        assert_eq!(text.lines().count(), 1);
+        assert!(text.starts_with("time,low,"));
+    }
+}
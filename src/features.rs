@@ -0,0 +1,47 @@
+use crate::chroma::{KeyEstimate, PITCH_CLASSES};
+use crate::onset::BeatEvent;
+use crate::stereo::StereoFeatures;
+use crate::tempo::TempoEstimate;
+use crate::triple_buffer::{Publisher, Reader};
+
+// Görselleştiricinin ıskaladığı kareler yüzünden vuruş kaybolmasın diye
+// anlık görüntüde son vuruşlar bu süre boyunca tutulur
+pub const BEAT_RETENTION: f64 = 1.0;
+
+// Bir analiz karesinin tamamı; renderer hep tek bir karenin tutarlı görüntüsünü alır
+#[derive(Clone, Debug, Default)]
+pub struct AudioFeatures {
+    // Pencere merkezinin parça başından itibaren saniyesi
+    pub timestamp: f64,
+    // `AnalyzerConfig::spectrum` ölçeğinde
+    pub spectrum: Vec<f32>,
+    // `AnalyzerConfig::bands` ile aynı sırada, yumuşatılmış ve kazancı ayarlanmış
+    pub bands: Vec<f32>,
+    // Son `BEAT_RETENTION` saniyedeki vuruşlar, eskiden yeniye
+    pub beats: Vec<BeatEvent>,
+    pub tempo: TempoEstimate,
+    pub stereo: StereoFeatures,
+    // C'den B'ye perde sınıfı enerjileri; en güçlüsü 1, sessizlikte hepsi 0
+    pub chroma: [f32; PITCH_CLASSES],
+    // Son birkaç saniyenin kromasından
+    pub key: KeyEstimate,
+    // 0..1, yaklaşan bir drop'tan önce yükselir; yalnızca önbellekten çalarken bilinir
+    pub anticipation: f32,
+}
+
+impl AudioFeatures {
+    // Parça bittiğinde ya da durduğunda görseller son kareye takılı kalmasın diye
+    pub fn silence(&mut self) {
+        self.spectrum.iter_mut().for_each(|value| *value = 0.0);
+        self.bands.iter_mut().for_each(|value| *value = 0.0);
+        self.beats.clear();
+        self.tempo = Default::default();
+        self.stereo.silence();
+        self.chroma = [0.0; PITCH_CLASSES];
+        self.key = KeyEstimate::default();
+        self.anticipation = 0.0;
+    }
+}
+
+pub type FeaturePublisher = Publisher<AudioFeatures>;
+pub type FeatureReader = Reader<AudioFeatures>;
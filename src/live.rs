@@ -0,0 +1,261 @@
+use rodio::Source;
+use std::any::Any;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Tüketici bu kadar parçanın gerisinde kalırsa yenileri atılır
+const QUEUE_CHUNKS: usize = 64;
+// Dolaşımdaki parçalar: dolu kuyruk, kaynağın okuduğu ve doldurulmakta olan
+const POOL_CHUNKS: usize = QUEUE_CHUNKS + 2;
+// Önceden ayrılan parça boyu, örnek; daha büyük bir tampon gelirse parça bir kez büyür
+const CHUNK_SAMPLES: usize = 8192;
+// Veri gelmezken durdurma isteğine bakma aralığı
+const STOP_POLL: Duration = Duration::from_millis(50);
+
+// Canlı bir girişin (yakalama kartı, boru) örneklerini `LiveSource`'a taşıyan uç.
+// Üretici iş parçacığı ya da ses geri çağrısı tarafından tutulur.
+#[derive(Clone)]
+pub struct LiveFeed {
+    sender: SyncSender<Vec<f32>>,
+    // Boş parçalar; kaynak okuduklarını geri yollar, böylece itmek bellek ayırmaz
+    spare: Arc<Mutex<Receiver<Vec<f32>>>>,
+    recycle: SyncSender<Vec<f32>>,
+    stop: Arc<AtomicBool>,
+    // Tüketici geride kaldığı için atılan bloklar
+    overruns: Arc<AtomicU64>,
+}
+
+impl LiveFeed {
+    // Ses geri çağrısından çağrılabilir: hiç beklemez ve parçayı havuzdan alır.
+    // Havuzda parça yoksa ya da kilit o an tutuluyorsa blok atılır. Yalnızca
+    // `CHUNK_SAMPLES`'tan büyük bir blok havuzdaki parçayı bir kez büyütür.
+    // Kaynak kapandıysa false.
+    pub fn push(&self, samples: impl IntoIterator<Item = f32>) -> bool {
+        let spare = self
+            .spare
+            .try_lock()
+            .ok()
+            .and_then(|spare| spare.try_recv().ok());
+        let Some(mut chunk) = spare else {
+            self.overruns.fetch_add(1, Ordering::Relaxed);
+            return !self.is_stopped();
+        };
+        chunk.clear();
+        chunk.extend(samples);
+        match self.sender.try_send(chunk) {
+            Ok(()) => true,
+            // Tüketici geride kaldı; parça atılır, belleği havuza döner
+            Err(TrySendError::Full(chunk)) => {
+                self.overruns.fetch_add(1, Ordering::Relaxed);
+                let _ = self.recycle.try_send(chunk);
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+
+    pub fn overruns(&self) -> u64 {
+        self.overruns.load(Ordering::Relaxed)
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+// Canlı girişi açık tutan taraf (yakalama akışı, okuyucu iş parçacığı);
+// bırakılınca besleme durur ve `LiveSource` biter
+pub struct LiveInput {
+    feed: LiveFeed,
+    // Yalnızca yaşam süresi için tutulur
+    _owner: Option<Box<dyn Any>>,
+    // Pencere başlığında gösterilen ad
+    pub name: String,
+}
+
+impl LiveInput {
+    pub fn new(feed: LiveFeed, owner: Option<Box<dyn Any>>, name: String) -> Self {
+        Self {
+            feed,
+            _owner: owner,
+            name,
+        }
+    }
+}
+
+impl Drop for LiveInput {
+    fn drop(&mut self) {
+        self.feed.stop();
+        let overruns = self.feed.overruns();
+        if overruns > 0 {
+            eprintln!(
+                "warning: dropped {} blocks from {} because analysis fell behind",
+                overruns, self.name
+            );
+        }
+    }
+}
+
+// Araya serpiştirilmiş örnekleri geldikçe veren kaynak; yeni veri yokken bekler.
+// Beslenen uç durdurulup kuyruk boşalınca ya da kapanınca biter.
+pub struct LiveSource {
+    receiver: Receiver<Vec<f32>>,
+    recycle: SyncSender<Vec<f32>>,
+    stop: Arc<AtomicBool>,
+    chunk: Vec<f32>,
+    position: usize,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl LiveSource {
+    pub fn new(channels: u16, sample_rate: u32) -> (Self, LiveFeed) {
+        let (sender, receiver) = mpsc::sync_channel(QUEUE_CHUNKS);
+        let (recycle, spare) = mpsc::sync_channel(POOL_CHUNKS);
+        for _ in 1..POOL_CHUNKS {
+            let _ = recycle.try_send(Vec::with_capacity(CHUNK_SAMPLES));
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        let source = Self {
+            receiver,
+            recycle: recycle.clone(),
+            stop: stop.clone(),
+            chunk: Vec::with_capacity(CHUNK_SAMPLES),
+            position: 0,
+            channels: channels.max(1),
+            sample_rate,
+        };
+        let feed = LiveFeed {
+            sender,
+            spare: Arc::new(Mutex::new(spare)),
+            recycle,
+            stop,
+            overruns: Arc::new(AtomicU64::new(0)),
+        };
+        (source, feed)
+    }
+}
+
+impl Iterator for LiveSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        while self.position >= self.chunk.len() {
+            // Durdurulduktan sonra da kuyruktakiler verilir; dosya sonuna gelen
+            // bir okuyucunun son parçaları kaybolmasın
+            let chunk = if self.stop.load(Ordering::Relaxed) {
+                self.receiver.try_recv().ok()?
+            } else {
+                match self.receiver.recv_timeout(STOP_POLL) {
+                    Ok(chunk) => chunk,
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => return None,
+                }
+            };
+            let used = std::mem::replace(&mut self.chunk, chunk);
+            let _ = self.recycle.try_send(used);
+            self.position = 0;
+        }
+        let sample = self.chunk[self.position];
+        self.position += 1;
+        Some(sample)
+    }
+}
+
+// Havuz boşken besleme kapanmayı kuyruktan anlayamaz; durdurma bayrağına bakar
+impl Drop for LiveSource {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Source for LiveSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Instant;
+
+    #[test]
+    fn chunks_arrive_in_order() {
+        let (source, feed) = LiveSource::new(2, 44100);
+        let producer = thread::spawn(move || {
+            for start in (0..20000).step_by(10) {
+                feed.push((start..start + 10).map(|i| i as f32));
+            }
+            feed.stop();
+        });
+        let samples: Vec<f32> = source.collect();
+        producer.join().unwrap();
+
+        // Geride kalan tüketicide parçalar atılabilir, ama sıra hiç bozulmaz
+        assert_eq!(
+            &samples[..10],
+            &(0..10).map(|i| i as f32).collect::<Vec<_>>()[..]
+        );
+        assert!(samples.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(samples.len() % 10, 0);
+    }
+
+    #[test]
+    fn full_queue_drops_without_blocking() {
+        let (source, feed) = LiveSource::new(1, 44100);
+        let started = Instant::now();
+        for i in 0..QUEUE_CHUNKS * 4 {
+            assert!(feed.push([i as f32]));
+        }
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(feed.overruns(), (QUEUE_CHUNKS * 3) as u64);
+
+        feed.stop();
+        let samples: Vec<f32> = source.collect();
+        assert_eq!(
+            samples,
+            (0..QUEUE_CHUNKS).map(|i| i as f32).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn stop_drains_queue_then_ends() {
+        let (mut source, feed) = LiveSource::new(1, 44100);
+        feed.push([1.0, 2.0]);
+        feed.push([3.0]);
+        feed.stop();
+        assert!(feed.is_stopped());
+        assert_eq!(
+            source.by_ref().take(3).collect::<Vec<_>>(),
+            vec![1.0, 2.0, 3.0]
+        );
+
+        // Boş ve durdurulmuş kuyrukta kaynak beklemeden biter
+        let started = Instant::now();
+        assert_eq!(source.next(), None);
+        assert!(started.elapsed() < STOP_POLL);
+
+        drop(source);
+        assert!(!feed.push([4.0]));
+    }
+}
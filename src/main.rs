@@ -1,122 +1,332 @@
+mod analysis;
+mod bands;
+mod binning;
+mod cache;
+mod capture;
+mod chroma;
+mod cli;
+mod decoder;
+mod envelope;
+mod error;
+mod export;
+mod features;
+mod live;
+mod onset;
+mod output;
+mod pcm;
+mod playback;
+mod playlist;
+mod scope;
 mod shaders;
+mod signal;
+mod stereo;
+mod tempo;
+mod transport;
+mod triple_buffer;
+mod window;
 
 use glfw::{Action, Context, Key};
 use nalgebra_glm as glm;
-use rand::Rng;
-use rodio::{Decoder, OutputStream, Source};
-use rustfft::{num_complex::Complex, FftPlanner};
-use std::fs::File;
-use std::io::BufReader;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rodio::Source;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use std::thread;
-
+use std::thread::JoinHandle;
+
+use crate::analysis::{AnalysisDriver, AnalyzerConfig, RING_CAPACITY};
+use crate::bands::{BandBindings, VisualParam, MAX_BANDS};
+use crate::cache::{CacheFill, FeatureCache, TimelinePlayer};
+use crate::capture::open_capture;
+use crate::chroma::PITCH_CLASSES;
+use crate::cli::{Command, Input, Options};
+use crate::decoder::{format_time, open_track, TrackInfo};
+use crate::error::VisualizerError;
+use crate::features::{AudioFeatures, FeaturePublisher, FeatureReader};
+use crate::live::{LiveInput, LiveSource};
+use crate::output::{AudioOutput, OutputMode};
+use crate::pcm::open_pcm;
+use crate::playback::{PlaybackClock, SampleRing, TeeSource};
+use crate::playlist::Playlist;
+use crate::scope::{Scene, ScopeTap, Vectorscope};
 use crate::shaders::{ShaderProgram, FRAGMENT_SHADER, VERTEX_SHADER};
-
-const SAMPLE_RATE: u32 = 44100;
-const FFT_SIZE: usize = 2048;
-const MIN_DB: f32 = -60.0;
-const MAX_DB: f32 = 0.0;
+use crate::signal::{Signal, SignalSource};
+use crate::tempo::{bar_wave, beat_pulse};
+use crate::transport::{Transport, TransportSource};
+use crate::triple_buffer::triple_buffer;
+
+// Vuruş tetikleyicilerinin kare başına sönümlenmesi ve kıvılcım ömrü (saniye)
+const FLASH_DECAY: f32 = 0.85;
+const KICK_DECAY: f32 = 0.8;
+const SPARK_LIFETIME: f32 = 1.2;
+// Stereo değerleri kare başına bu oranda hedefe yaklaşır
+const STEREO_SMOOTHING: f32 = 0.08;
+// Tamamen bir yana kaymış bir spektrum diliminin şekli ne kadar kaydırdığı
+const STEREO_DRIFT: f32 = 2.0;
+// Shader'a giden sol/sağ spektrumların dilim sayısı
+const SHADER_SPECTRUM_SLOTS: usize = 32;
+// Kroma ve anahtar kare başına bu oranda hedefe yaklaşır; renkler her hop'ta titremesin
+const CHROMA_SMOOTHING: f32 = 0.1;
+// Bu anahtar güveninin altı tonal sayılmaz, üstü giderek daha tonaldır
+const TONAL_CONFIDENCE: f32 = 0.4;
 
 struct AudioAnalyzer {
-    spectrum: Arc<Mutex<Vec<f32>>>,
-    bass_energy: Arc<Mutex<f32>>,
-    mid_energy: Arc<Mutex<f32>>,
-    high_energy: Arc<Mutex<f32>>,
-    _stream: Option<OutputStream>,
+    // Parça çalmıyorken burada, çalarken analiz iş parçacığındadır
+    publisher: Option<FeaturePublisher>,
+    config: AnalyzerConfig,
+    // None ise önbellek kapalı
+    cache_dir: Option<PathBuf>,
+    volume: f32,
+    output_mode: OutputMode,
+    clock: Option<Arc<PlaybackClock>>,
+    transport: Option<Arc<Transport>>,
+    // Döngü başlangıcı işaretlendi, bitişi bekleniyor
+    loop_start: Option<f64>,
+    worker: Option<JoinHandle<FeaturePublisher>>,
+    // Canlı girişte açık tutulan kaynak
+    live_input: Option<LiveInput>,
+    output: Option<AudioOutput>,
+    // Vektörskobun okuduğu, analizle aynı örnek akışı
+    scope: Option<ScopeTap>,
+    // Çalan parça için arka planda doldurulan önbellek; parça durunca iptal edilir
+    cache_fill: Option<CacheFill>,
 }
 
 impl AudioAnalyzer {
-    fn new() -> Self {
+    fn new(
+        config: AnalyzerConfig,
+        cache_dir: Option<PathBuf>,
+        volume: f32,
+        output_mode: OutputMode,
+        publisher: FeaturePublisher,
+    ) -> Self {
         Self {
-            spectrum: Arc::new(Mutex::new(vec![0.0; FFT_SIZE / 2])),
-            bass_energy: Arc::new(Mutex::new(0.0)),
-            mid_energy: Arc::new(Mutex::new(0.0)),
-            high_energy: Arc::new(Mutex::new(0.0)),
-            _stream: None,
+            publisher: Some(publisher),
+            config,
+            cache_dir,
+            volume,
+            output_mode,
+            clock: None,
+            transport: None,
+            loop_start: None,
+            worker: None,
+            live_input: None,
+            output: None,
+            scope: None,
+            cache_fill: None,
         }
     }
 
-    fn start_audio_processing(&mut self, file_path: &str) {
-        let (stream, stream_handle) = OutputStream::try_default().unwrap();
-
-        // Müzik çalma için
-        let file_play = BufReader::new(File::open(file_path).unwrap());
-        let source_play = Decoder::new(file_play).unwrap();
-        let _ = stream_handle.play_raw(source_play.convert_samples());
-
-        // FFT analizi için
-        let file_analyze = BufReader::new(File::open(file_path).unwrap());
-        let source_analyze = Decoder::new(file_analyze).unwrap();
-        let samples: Vec<f32> = source_analyze.convert_samples().collect();
-
-        self._stream = Some(stream);
-
-        let spectrum = self.spectrum.clone();
-        let bass = self.bass_energy.clone();
-        let mid = self.mid_energy.clone();
-        let high = self.high_energy.clone();
-
-        thread::spawn(move || {
-            let mut planner = FftPlanner::new();
-            let fft = planner.plan_fft_forward(FFT_SIZE);
-            let mut buffer = vec![Complex::new(0.0, 0.0); FFT_SIZE];
-            let mut pos = 0;
-
-            loop {
-                for i in 0..FFT_SIZE {
-                    if pos + i < samples.len() {
-                        buffer[i] = Complex::new(samples[pos + i], 0.0);
-                    } else {
-                        buffer[i] = Complex::new(0.0, 0.0);
-                    }
-                }
+    // Çalan parçayı durdurup `file_path`'i baştan başlatır
+    fn start_audio_processing(&mut self, file_path: &Path) -> Result<TrackInfo, VisualizerError> {
+        self.stop();
+        let (source, info) = open_track(file_path)?;
+        self.start_source(source, Some(file_path))?;
+        Ok(info)
+    }
 
-                fft.process(&mut buffer);
+    // Canlı girişler çalınmaz, yalnızca analiz edilir; kontrol tuşları etkisizdir.
+    // Girişin adını döndürür.
+    fn start_live(
+        &mut self,
+        input: LiveInput,
+        source: LiveSource,
+    ) -> Result<String, VisualizerError> {
+        self.stop();
+        let name = input.name.clone();
+        self.live_input = Some(input);
+        self.start_source(source, None)?;
+        Ok(name)
+    }
 
-                let mut spectrum_data = vec![0.0; FFT_SIZE / 2];
-                for i in 0..FFT_SIZE / 2 {
-                    let magnitude = (buffer[i].norm() / FFT_SIZE as f32).log10() * 20.0;
-                    spectrum_data[i] = (magnitude - MIN_DB) / (MAX_DB - MIN_DB);
-                }
+    // Test sinyalleri dosyalar gibi çalınır; duraklatma, atlama ve ses düzeyi çalışır
+    fn start_signal(
+        &mut self,
+        signal: Signal,
+        duration: Option<f64>,
+    ) -> Result<(), VisualizerError> {
+        self.stop();
+        self.start_source(SignalSource::new(signal, duration), None)
+    }
 
-                let mut bass_sum = 0.0;
-                let mut mid_sum = 0.0;
-                let mut high_sum = 0.0;
-
-                for i in 0..FFT_SIZE / 2 {
-                    let freq = i as f32 * SAMPLE_RATE as f32 / FFT_SIZE as f32;
-                    if freq < 250.0 {
-                        bass_sum += spectrum_data[i];
-                    } else if freq < 2000.0 {
-                        mid_sum += spectrum_data[i];
-                    } else {
-                        high_sum += spectrum_data[i];
-                    }
+    // Tek kaynak: çalınan (ya da tüketilen) örnekler aynı anda FFT halkasına da kopyalanır.
+    // Önbellek yalnızca dosyalar için kullanılır.
+    fn start_source<S>(
+        &mut self,
+        source: S,
+        file_path: Option<&Path>,
+    ) -> Result<(), VisualizerError>
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        let sample_rate = source.sample_rate();
+        let clock = Arc::new(PlaybackClock::new(source.channels()));
+        let ring = Arc::new(Mutex::new(SampleRing::new(RING_CAPACITY)));
+        let tee = TeeSource::new(source, clock.clone(), ring.clone());
+        let latency_frames = self.config.latency_ms as i64 * sample_rate as i64 / 1000;
+        self.scope = Some(ScopeTap::new(clock.clone(), ring.clone(), latency_frames));
+        if self.live_input.is_some() {
+            self.output = Some(AudioOutput::drain(tee));
+        } else {
+            let transport = Arc::new(Transport::new(clock.clone(), sample_rate, self.volume));
+            let controlled = TransportSource::new(tee, transport.clone());
+            self.output = Some(AudioOutput::play(self.output_mode, controlled)?);
+            self.transport = Some(transport);
+        }
+
+        let Some(publisher) = self.publisher.take() else {
+            return Ok(());
+        };
+        // Önbellekte varsa kareler yalnızca saate göre okunur; yoksa canlı analiz
+        // edilir ve önbellek bir sonraki açılış için arka planda doldurulur
+        let cache = file_path
+            .zip(self.cache_dir.as_deref())
+            .and_then(|(file_path, dir)| {
+                FeatureCache::new(dir, file_path, &self.config)
+                    .ok()
+                    .map(|cache| (cache, file_path))
+            });
+        let worker = match cache
+            .as_ref()
+            .and_then(|(cache, _)| cache.load(&self.config, sample_rate))
+        {
+            Some(timeline) => {
+                TimelinePlayer::new(timeline, &self.config, sample_rate, clock.clone())
+                    .spawn(publisher)
+            }
+            None => {
+                if let Some((cache, file_path)) = cache {
+                    self.cache_fill =
+                        cache.fill_in_background(file_path.to_path_buf(), self.config.clone());
                 }
+                AnalysisDriver::new(&self.config, sample_rate, clock.clone(), ring).spawn(publisher)
+            }
+        };
+        self.clock = Some(clock);
+        self.worker = Some(worker);
 
-                *spectrum.lock().unwrap() = spectrum_data;
-                *bass.lock().unwrap() = bass_sum / 250.0;
-                *mid.lock().unwrap() = mid_sum / 1750.0;
-                *high.lock().unwrap() = high_sum / (FFT_SIZE as f32 / 2.0 - 2000.0);
+        Ok(())
+    }
 
-                pos += FFT_SIZE / 2;
-                if pos >= samples.len() {
-                    pos = 0;
-                }
+    fn scope_tap(&mut self) -> Option<&mut ScopeTap> {
+        self.scope.as_mut()
+    }
+
+    // Parça sonuna gelindi mi; hiç parça çalmıyorsa da true
+    fn is_finished(&self) -> bool {
+        self.clock.as_ref().is_none_or(|clock| clock.is_finished())
+    }
+
+    fn toggle_pause(&self) -> Option<bool> {
+        let transport = self.transport.as_ref()?;
+        transport.set_paused(!transport.is_paused());
+        Some(transport.is_paused())
+    }
+
+    // Hedef konumu döndürür
+    fn seek_by(&self, seconds: f64) -> Option<f64> {
+        let transport = self.transport.as_ref()?;
+        let target = (transport.position() + seconds).max(0.0);
+        transport.seek(target);
+        Some(target)
+    }
+
+    // Ses düzeyi sonraki parçalarda da korunur. Çalınmayan girişlerde (yakalama, PCM)
+    // ses düzeyi yoktur; None döner ve düzey değişmez.
+    fn change_volume(&mut self, delta: f32) -> Option<f32> {
+        let transport = self.transport.as_ref()?;
+        self.volume = ((self.volume + delta) * 10.0).round().max(0.0) / 10.0;
+        transport.set_volume(self.volume);
+        Some(self.volume)
+    }
+
+    // Yeni bir A noktası eski döngüyü kaldırır
+    fn mark_loop_start(&mut self) -> Option<f64> {
+        let transport = self.transport.as_ref()?;
+        transport.set_loop(None);
+        self.loop_start = Some(transport.position());
+        self.loop_start
+    }
+
+    // B noktası A'dan sonraysa döngü başlar ve çalma A'ya döner
+    fn mark_loop_end(&mut self) -> Option<(f64, f64)> {
+        let transport = self.transport.as_ref()?;
+        let region = (self.loop_start?, transport.position());
+        if region.1 <= region.0 {
+            return None;
+        }
+        transport.set_loop(Some(region));
+        Some(region)
+    }
+
+    fn clear_loop(&mut self) {
+        self.loop_start = None;
+        if let Some(transport) = &self.transport {
+            transport.set_loop(None);
+        }
+    }
 
-                thread::sleep(std::time::Duration::from_millis(16));
+    // Çıkış kapatılır ve analiz iş parçacığının yayımcıyı geri vermesi beklenir
+    fn stop(&mut self) {
+        // Önce giriş kapanır ki bekleyen kaynak bitsin ve çıkış iş parçacığı katılabilsin
+        self.live_input = None;
+        self.output = None;
+        self.transport = None;
+        self.loop_start = None;
+        self.scope = None;
+        self.cache_fill = None;
+        if let Some(clock) = self.clock.take() {
+            clock.finish();
+        }
+        if let Some(worker) = self.worker.take() {
+            match worker.join() {
+                Ok(publisher) => self.publisher = Some(publisher),
+                Err(_) => eprintln!("warning: analysis thread panicked"),
             }
-        });
+        }
+    }
+}
+
+impl Drop for AudioAnalyzer {
+    fn drop(&mut self) {
+        self.stop();
     }
 }
 
 struct Visualizer {
     shader_program: ShaderProgram,
     time: f32,
-    audio_analyzer: Arc<AudioAnalyzer>,
+    features: FeatureReader,
+    // Tetiklenmiş en yeni vuruşun zamanı; anlık görüntüde bundan eskiler atlanır
+    last_beat: f64,
+    // Son okunan karenin zamanı; geriye gitmesi atlama ya da yeni parça demektir
+    last_timestamp: f64,
     shapes: Vec<Shape>,
     vao: u32,
     vbo: u32,
+    aspect_ratio: f32,
+    bindings: BandBindings,
+    rng: StdRng,
+    flash: f32,
+    camera_kick: f32,
+    sparks: Vec<Spark>,
+    // Yumuşatılmış stereo genişlik; tünelin açılmasını sürer
+    stereo_width: f32,
+    scene: Scene,
+    scope: Vectorscope,
+    // Yumuşatılmış kroma ve anahtar; renk paletini sürer
+    chroma: [f32; PITCH_CLASSES],
+    // 0 atonal, 1 net bir anahtar
+    tonality: f32,
+    // 0 majör, 1 minör; tonal olmayan müzikte 0'a döner
+    key_minor: f32,
+}
+
+// Vuruşla doğan, büyüyerek sönen şekil
+struct Spark {
+    position: glm::Vec3,
+    age: f32,
+    strength: f32,
 }
 
 struct Shape {
@@ -125,10 +335,28 @@ struct Shape {
     color: glm::Vec4,
     rotation: f32,
     energy_response: f32,
+    // Şeklin tepki verdiği spektrum dilimi (0..1, düşükten yükseğe)
+    spectrum_slot: f32,
+    // Diliminin geldiği yana doğru yumuşatılmış kayma, -1..1
+    drift: f32,
+    // Halkadaki konumu; her halkada on iki şekil, her biri bir perde sınıfı
+    pitch_class: usize,
 }
 
 impl Visualizer {
-    fn new(audio_analyzer: Arc<AudioAnalyzer>) -> Self {
+    fn new(
+        features: FeatureReader,
+        (width, height): (i32, i32),
+        seed: Option<u64>,
+        bindings: BandBindings,
+        scene: Scene,
+    ) -> Result<Self, VisualizerError> {
+        if !gl::GenVertexArrays::is_loaded() {
+            return Err(VisualizerError::GlInit(
+                "OpenGL 3.3 functions could not be loaded".to_string(),
+            ));
+        }
+
         let (vao, vbo) = unsafe {
             gl::Enable(gl::DEPTH_TEST);
             gl::Enable(gl::BLEND);
@@ -172,11 +400,14 @@ impl Visualizer {
             (vao, vbo)
         };
 
-        let shader_program = ShaderProgram::new(VERTEX_SHADER, FRAGMENT_SHADER)
-            .expect("Failed to create shader program");
+        let shader_program = ShaderProgram::new(VERTEX_SHADER, FRAGMENT_SHADER)?;
+        let scope = Vectorscope::new(width, height)?;
 
         let mut shapes = Vec::new();
-        let mut rng = rand::thread_rng();
+        let mut rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
 
         // İç içe tüneller oluştur
         for tunnel_id in 0..3 {
@@ -214,6 +445,9 @@ impl Visualizer {
                         ),
                         rotation: angle + (tunnel_id as f32 * std::f32::consts::PI / 3.0),
                         energy_response: rng.gen_range(0.8..2.0),
+                        spectrum_slot: j as f32 / ring_count as f32,
+                        drift: 0.0,
+                        pitch_class: j % PITCH_CLASSES,
                     });
 
                     // İç şekiller ekle
@@ -236,42 +470,143 @@ impl Visualizer {
                             ),
                             rotation: -angle * 2.0,
                             energy_response: rng.gen_range(1.0..2.5),
+                            spectrum_slot: j as f32 / ring_count as f32,
+                            drift: 0.0,
+                            pitch_class: j % PITCH_CLASSES,
                         });
                     }
                 }
             }
         }
 
-        Self {
+        Ok(Self {
             shader_program,
             time: 0.0,
-            audio_analyzer,
+            features,
+            last_beat: f64::NEG_INFINITY,
+            last_timestamp: 0.0,
             shapes,
             vao,
             vbo,
-        }
+            aspect_ratio: width as f32 / height.max(1) as f32,
+            bindings,
+            rng,
+            flash: 0.0,
+            camera_kick: 0.0,
+            sparks: Vec::new(),
+            stereo_width: 0.0,
+            scene,
+            scope,
+            chroma: [0.0; PITCH_CLASSES],
+            tonality: 0.0,
+            key_minor: 0.0,
+        })
+    }
+
+    fn next_scene(&mut self) -> Scene {
+        self.scene = self.scene.next();
+        self.scope.reset();
+        self.scene
     }
 
-    fn render(&mut self) {
+    // `scope` yalnızca vektörskop sahnesinde okunur
+    fn render(&mut self, scope: Option<&mut ScopeTap>) {
         self.time += 0.016;
 
-        let bass = *self.audio_analyzer.bass_energy.lock().unwrap();
-        let mid = *self.audio_analyzer.mid_energy.lock().unwrap();
-        let high = *self.audio_analyzer.high_energy.lock().unwrap();
+        // Tek bir analiz karesi; beklemeden en son yayımlanan okunur
+        let features = self.features.read();
+        let energies = &features.bands;
+        let band = |param| self.bindings.value(param, energies);
+        let spectrum = &features.spectrum;
+        let tempo = features.tempo;
+        // Drop yaklaşırken sahne kararır, geri çekilir ve şekiller büzülür
+        let anticipation = features.anticipation;
+        let stereo = &features.stereo;
+        self.stereo_width += (stereo.width - self.stereo_width) * STEREO_SMOOTHING;
+        // Sıradan bir miksin genişliği tüneli olduğu gibi bırakır; geniş bir
+        // breakdown onu açar
+        let opening = 1.0 + ((self.stereo_width - 0.1) * 2.5).clamp(0.0, 1.0) * 0.6;
+        for (smoothed, &value) in self.chroma.iter_mut().zip(&features.chroma) {
+            *smoothed += (value - *smoothed) * CHROMA_SMOOTHING;
+        }
+        let tonality = ((features.key.confidence - TONAL_CONFIDENCE) / (1.0 - TONAL_CONFIDENCE))
+            .clamp(0.0, 1.0);
+        self.tonality += (tonality - self.tonality) * CHROMA_SMOOTHING;
+        let minor = if features.key.minor { tonality } else { 0.0 };
+        self.key_minor += (minor - self.key_minor) * CHROMA_SMOOTHING;
+        // Zaman geriye gittiyse eski vuruş zamanı yenilerini gizlemesin
+        if features.timestamp < self.last_timestamp {
+            self.last_beat = f64::NEG_INFINITY;
+        }
+        self.last_timestamp = features.timestamp;
+
+        // Vuruş tetikleyicileri: flaş, kamera sarsıntısı ve kıvılcım
+        self.flash *= FLASH_DECAY;
+        self.camera_kick *= KICK_DECAY;
+        for spark in &mut self.sparks {
+            spark.age += 0.016;
+        }
+        self.sparks.retain(|spark| spark.age < SPARK_LIFETIME);
+
+        let mut spawns = Vec::new();
+        let last_beat = self.last_beat;
+        for beat in features.beats.iter().filter(|beat| beat.time > last_beat) {
+            self.last_beat = beat.time;
+            let impact = 0.4 + 0.6 * beat.strength;
+            if beat.band == self.bindings.band(VisualParam::Flash) {
+                self.flash = self.flash.max(impact);
+            }
+            if beat.band == self.bindings.band(VisualParam::Kick) {
+                self.camera_kick = self.camera_kick.max(impact);
+            }
+            if beat.band == self.bindings.band(VisualParam::Spawn) {
+                spawns.push(impact);
+            }
+        }
+
+        if self.scene == Scene::Vectorscope {
+            self.scope.render(scope, self.flash);
+            return;
+        }
 
         unsafe {
-            gl::ClearColor(0.0, 0.0, 0.1, 1.0);
+            gl::BindVertexArray(self.vao);
+            let dim = 1.0 - anticipation * 0.8;
+            gl::ClearColor(
+                self.flash * 0.25,
+                self.flash * 0.25,
+                (0.1 + self.flash * 0.3) * dim,
+                1.0,
+            );
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
 
-            // Kamera hareketi
-            let forward_speed = 1.5 + bass * 2.0;
+            // Kamera hareketi; tempo güvenilirse salınım ölçüye kilitlenir
+            let lock = tempo.confidence;
+            let sway_y = (self.time * 0.3).sin() * (1.0 - lock) + bar_wave(tempo.bar_phase) * lock;
+            let sway_x =
+                (self.time * 0.2).cos() * (1.0 - lock) + bar_wave(tempo.bar_phase + 0.25) * lock;
+            let forward_speed = 1.5 + band(VisualParam::Speed) * 2.0;
             let camera_z = -50.0 + self.time * forward_speed;
-            let camera_y = 2.0 + (self.time * 0.3).sin() * 2.0;
-            let camera_x = (self.time * 0.2).cos() * 4.0;
+            let camera_y = 2.0 + sway_y * 2.0 + self.camera_kick * 0.8;
+            let camera_x = sway_x * 4.0;
+
+            for strength in spawns {
+                let angle = self.rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+                let radius = self.rng.gen_range(2.0..6.0);
+                self.sparks.push(Spark {
+                    position: glm::vec3(
+                        camera_x + angle.cos() * radius,
+                        camera_y + angle.sin() * radius,
+                        camera_z + 25.0,
+                    ),
+                    age: 0.0,
+                    strength,
+                });
+            }
 
             let target_z = camera_z + 10.0;
-            let target_y = camera_y + (mid * 2.0).sin() * 3.0;
-            let target_x = camera_x + (high * 2.0).cos() * 3.0;
+            let target_y = camera_y + (band(VisualParam::LookY) * 2.0).sin() * 3.0;
+            let target_x = camera_x + (band(VisualParam::LookX) * 2.0).cos() * 3.0;
 
             let up_vector = glm::vec3(
                 (self.time * 0.1).sin() * 0.2,
@@ -285,15 +620,53 @@ impl Visualizer {
                 &up_vector,
             );
 
-            let projection = glm::perspective(70.0f32.to_radians(), 800.0 / 600.0, 0.1, 100.0);
+            let fov = 70.0 - self.camera_kick * 10.0 + anticipation * 20.0;
+            let projection = glm::perspective(fov.to_radians(), self.aspect_ratio, 0.1, 100.0);
 
             self.shader_program.use_program();
             self.shader_program.set_mat4("view", &view);
             self.shader_program.set_mat4("projection", &projection);
             self.shader_program.set_float("time", self.time);
-            self.shader_program.set_float("bassEnergy", bass);
-            self.shader_program.set_float("midEnergy", mid);
-            self.shader_program.set_float("highEnergy", high);
+            self.shader_program.set_float("flash", self.flash);
+            self.shader_program.set_float("bpm", tempo.bpm);
+            self.shader_program.set_float("beatPhase", tempo.beat_phase);
+            self.shader_program.set_float("barPhase", tempo.bar_phase);
+            self.shader_program
+                .set_float("tempoConfidence", tempo.confidence);
+            self.shader_program
+                .set_float("bassEnergy", band(VisualParam::ShaderBass));
+            self.shader_program
+                .set_float("midEnergy", band(VisualParam::ShaderMid));
+            self.shader_program
+                .set_float("highEnergy", band(VisualParam::ShaderHigh));
+            let band_count = energies.len().min(MAX_BANDS);
+            self.shader_program
+                .set_float_array("bands", &energies[..band_count]);
+            self.shader_program.set_int("bandCount", band_count as i32);
+            self.shader_program.set_float("stereoMid", stereo.mid);
+            self.shader_program.set_float("stereoSide", stereo.side);
+            self.shader_program
+                .set_float("stereoWidth", self.stereo_width);
+            self.shader_program
+                .set_float("stereoBalance", stereo.balance);
+            let pan_count = stereo.band_pan.len().min(MAX_BANDS);
+            self.shader_program
+                .set_float_array("bandPan", &stereo.band_pan[..pan_count]);
+            let mut slots = [0.0; SHADER_SPECTRUM_SLOTS];
+            shader_slots(&stereo.left_spectrum, &mut slots);
+            self.shader_program.set_float_array("spectrumLeft", &slots);
+            shader_slots(&stereo.right_spectrum, &mut slots);
+            self.shader_program.set_float_array("spectrumRight", &slots);
+            self.shader_program.set_float_array("chroma", &self.chroma);
+            self.shader_program.set_float("keyMinor", self.key_minor);
+            self.shader_program.set_float("tonality", self.tonality);
+
+            let scale_energy = band(VisualParam::Scale);
+            let red = band(VisualParam::Red);
+            let green = band(VisualParam::Green);
+            let blue = band(VisualParam::Blue);
+            let beat_scale = (1.0 + beat_pulse(tempo.beat_phase) * 0.2 * tempo.confidence)
+                * (1.0 - anticipation * 0.3);
 
             for shape in &mut self.shapes {
                 let mut model = glm::Mat4::identity();
@@ -304,8 +677,23 @@ impl Visualizer {
                     pos.z -= 180.0;
                 }
 
-                let energy = bass * shape.energy_response;
-                let scale = shape.scale * (1.0 + energy);
+                let slot = (shape.spectrum_slot * spectrum.len() as f32) as usize;
+                let slot_level = spectrum.get(slot).copied().unwrap_or(0.0);
+                let energy = scale_energy * shape.energy_response + slot_level * 0.5;
+
+                // Şekil, dilimindeki sesin geldiği yana süzülür; 15 dB fark tam kaymadır
+                let slot_pan = match (
+                    stereo.left_spectrum.get(slot),
+                    stereo.right_spectrum.get(slot),
+                ) {
+                    (Some(left), Some(right)) => ((right - left) * 4.0).clamp(-1.0, 1.0),
+                    _ => 0.0,
+                };
+                let target = (slot_pan + stereo.balance * 0.5).clamp(-1.0, 1.0);
+                shape.drift += (target - shape.drift) * STEREO_SMOOTHING;
+                pos.x = pos.x * opening + shape.drift * STEREO_DRIFT;
+                pos.y *= opening;
+                let scale = shape.scale * (1.0 + energy) * beat_scale;
 
                 model = glm::translate(&model, &pos);
                 model = glm::rotate(
@@ -315,10 +703,15 @@ impl Visualizer {
                 );
                 model = glm::scale(&model, &glm::vec3(scale, scale, scale));
 
+                // Perde sınıfı çalındıkça şekil onun rengine boyanır; minörde koyulaşır
+                let tint = pitch_color(shape.pitch_class);
+                let harmony = self.chroma[shape.pitch_class] * self.tonality * 0.6;
+                let base =
+                    glm::lerp(&shape.color.xyz(), &tint, harmony) * (1.0 - self.key_minor * 0.3);
                 let color = glm::vec4(
-                    shape.color.x + mid * 0.3 * (self.time * 1.5 + pos.x).sin(),
-                    shape.color.y + high * 0.3 * (self.time * 2.0 + pos.y).sin(),
-                    shape.color.z + bass * 0.3 * (self.time * 1.0 + pos.z).sin(),
+                    base.x + red * 0.3 * (self.time * 1.5 + pos.x).sin(),
+                    base.y + green * 0.3 * (self.time * 2.0 + pos.y).sin(),
+                    base.z + blue * 0.3 * (self.time * 1.0 + pos.z).sin(),
                     shape.color.w,
                 );
 
@@ -328,6 +721,23 @@ impl Visualizer {
 
                 gl::DrawArrays(gl::TRIANGLES, 0, 36);
             }
+
+            for spark in &self.sparks {
+                let life = spark.age / SPARK_LIFETIME;
+                let scale = 0.3 + life * 3.0 * spark.strength;
+
+                let mut model = glm::Mat4::identity();
+                model = glm::translate(&model, &spark.position);
+                model = glm::rotate(&model, self.time * 2.0, &glm::vec3(1.0, 1.0, 0.0));
+                model = glm::scale(&model, &glm::vec3(scale, scale, scale));
+
+                self.shader_program.set_mat4("model", &model);
+                self.shader_program
+                    .set_vec4("color", &glm::vec4(1.0, 1.0, 1.0, (1.0 - life) * 0.8));
+                self.shader_program.set_float("audioEnergy", spark.strength);
+
+                gl::DrawArrays(gl::TRIANGLES, 0, 36);
+            }
         }
     }
 }
@@ -342,7 +752,174 @@ impl Drop for Visualizer {
 }
 
 fn main() {
-    let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
+    let options = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(Command::Run(options)) => options,
+        Ok(Command::Analyze(options)) => {
+            let config = options.analyzer_config();
+            let format = options.export_format();
+            let out = options.out.as_deref();
+            let result = match &options.input {
+                Input::Signal(signal) => export::export_signal(
+                    signal.clone(),
+                    options.analyze_duration(),
+                    &config,
+                    format,
+                    out,
+                ),
+                _ => export::export_features(&options.tracks[0], &config, format, out),
+            };
+            if let Err(err) = result {
+                eprintln!("error: {}", err);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Ok(Command::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Ok(Command::ListDevices(host)) => {
+            if let Err(err) = capture::list_devices(host.as_deref()) {
+                eprintln!("error: {}", err);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(err) = run(options) {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Step {
+    Forward,
+    Back,
+}
+
+// Listenin o anki parçasını çalar; açılamayan parçalar uyarıyla atlanıp `step`
+// yönünde sıradaki denenir. Dosyaya özgü olmayan hatalar (ses çıkışı gibi) hemen döner.
+fn play_current(
+    audio_analyzer: &mut AudioAnalyzer,
+    playlist: &mut Playlist,
+    step: Step,
+) -> Result<TrackInfo, VisualizerError> {
+    let mut first_error = None;
+    for _ in 0..playlist.track_count() {
+        let err = match audio_analyzer.start_audio_processing(playlist.current()) {
+            Ok(info) => return Ok(info),
+            Err(
+                err @ (VisualizerError::FileNotFound(_)
+                | VisualizerError::FileOpen(..)
+                | VisualizerError::Decode(..)),
+            ) => err,
+            Err(err) => return Err(err),
+        };
+        eprintln!("warning: skipping track: {}", err);
+        first_error.get_or_insert(err);
+
+        let moved = match step {
+            Step::Forward => playlist.skip_forward().is_some(),
+            Step::Back => {
+                let skipped = playlist.current().to_path_buf();
+                playlist.skip_back() != skipped
+            }
+        };
+        if !moved {
+            break;
+        }
+    }
+    Err(first_error.unwrap_or(VisualizerError::EmptyPlaylist))
+}
+
+// Çalma kontrol tuşları; tuş bunlardan biriyse true
+fn transport_key(audio_analyzer: &mut AudioAnalyzer, key: Key) -> bool {
+    let seek = match key {
+        Key::Left => -5.0,
+        Key::Right => 5.0,
+        Key::Down => -30.0,
+        Key::Up => 30.0,
+        _ => 0.0,
+    };
+    match key {
+        Key::Left | Key::Right | Key::Down | Key::Up => {
+            if let Some(target) = audio_analyzer.seek_by(seek) {
+                eprintln!("seek: {}", format_time(target));
+            }
+        }
+        Key::Equal | Key::KpAdd => {
+            if let Some(volume) = audio_analyzer.change_volume(0.1) {
+                eprintln!("volume: {:.0}%", volume * 100.0);
+            }
+        }
+        Key::Minus | Key::KpSubtract => {
+            if let Some(volume) = audio_analyzer.change_volume(-0.1) {
+                eprintln!("volume: {:.0}%", volume * 100.0);
+            }
+        }
+        _ => return false,
+    }
+    true
+}
+
+// Çalma listesi tuşları; başka bir parçaya geçilecekse yönü döner
+fn playlist_key(playlist: &mut Playlist, key: Key) -> Option<Step> {
+    match key {
+        Key::N => playlist.skip_forward().map(|_| Step::Forward),
+        Key::P => {
+            playlist.skip_back();
+            Some(Step::Back)
+        }
+        Key::S => {
+            playlist.set_shuffle(!playlist.shuffle());
+            eprintln!("shuffle: {}", if playlist.shuffle() { "on" } else { "off" });
+            None
+        }
+        Key::R => {
+            playlist.set_repeat(playlist.repeat().next());
+            eprintln!("repeat: {}", playlist.repeat());
+            None
+        }
+        _ => None,
+    }
+}
+
+// Yeniden örneklenmiş spektrumu sabit boyutlu shader dizisine indirger; her dilim
+// kapsadığı değerlerin ortalamasıdır
+fn shader_slots(spectrum: &[f32], out: &mut [f32]) {
+    let slots = out.len();
+    for (index, slot) in out.iter_mut().enumerate() {
+        let start = index * spectrum.len() / slots;
+        let end = ((index + 1) * spectrum.len() / slots).max(start + 1);
+        *slot = spectrum
+            .get(start..end.min(spectrum.len()))
+            .filter(|values| !values.is_empty())
+            .map_or(0.0, |values| {
+                values.iter().sum::<f32>() / values.len() as f32
+            });
+    }
+}
+
+// Fragment shader'daki rainbow() paletiyle aynı; perde sınıfları beşliler çemberinde
+fn pitch_color(pitch_class: usize) -> glm::Vec3 {
+    let hue = (pitch_class * 7 % PITCH_CLASSES) as f32 / PITCH_CLASSES as f32;
+    let channel = |offset: f32| 0.5 + 0.5 * (std::f32::consts::TAU * (hue + offset)).cos();
+    glm::vec3(channel(0.0), channel(0.33), channel(0.67))
+}
+
+fn window_title(label: &str) -> String {
+    format!("{} - Berlin Techno Visualizer", label)
+}
+
+fn run(options: Options) -> Result<(), VisualizerError> {
+    let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS)
+        .map_err(|err| VisualizerError::GlInit(format!("GLFW: {:?}", err)))?;
 
     glfw.window_hint(glfw::WindowHint::ContextVersion(3, 3));
     glfw.window_hint(glfw::WindowHint::OpenGlProfile(
@@ -350,40 +927,143 @@ fn main() {
     ));
 
     let (mut window, events) = glfw
-        .create_window(
-            800,
-            600,
-            "Berlin Techno Visualizer",
-            glfw::WindowMode::Windowed,
-        )
-        .expect("Failed to create GLFW window");
+        .with_primary_monitor(|glfw, monitor| {
+            // Tam ekranda monitörün kendi çözünürlüğü kullanılır
+            let (width, height, mode) = match monitor.filter(|_| options.fullscreen) {
+                Some(monitor) => {
+                    let (width, height) = monitor
+                        .get_video_mode()
+                        .map(|mode| (mode.width, mode.height))
+                        .unwrap_or((options.width, options.height));
+                    (width, height, glfw::WindowMode::FullScreen(monitor))
+                }
+                None => (options.width, options.height, glfw::WindowMode::Windowed),
+            };
+            glfw.create_window(width, height, "Berlin Techno Visualizer", mode)
+        })
+        .ok_or_else(|| VisualizerError::GlInit("failed to create GLFW window".to_string()))?;
 
     window.make_current();
     window.set_key_polling(true);
 
     gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
 
-    let mut audio_analyzer = Arc::new(AudioAnalyzer::new());
-    Arc::get_mut(&mut audio_analyzer)
-        .unwrap()
-        .start_audio_processing(
-            "src/Daft Punk - Veridis Quo (Official Video) (online-audio-converter.com).mp3",
-        );
-
-    let mut visualizer = Visualizer::new(audio_analyzer);
+    let (publisher, features) = triple_buffer(AudioFeatures::default());
+    let mut audio_analyzer = AudioAnalyzer::new(
+        options.analyzer_config(),
+        options.cache_dir(),
+        options.volume,
+        options.output_mode(),
+        publisher,
+    );
+    // Canlı girişlerde çalma listesi yoktur
+    let mut playlist = None;
+    let label = match &options.input {
+        Input::Files => {
+            let mut list = Playlist::from_paths(&options.tracks)?;
+            list.set_repeat(options.repeat);
+            if options.shuffle {
+                list.start_shuffled();
+            }
+            let track = play_current(&mut audio_analyzer, &mut list, Step::Forward)?;
+            track.label(playlist.insert(list).current())
+        }
+        Input::Capture(device) => {
+            let (input, source) = open_capture(options.host.as_deref(), device)?;
+            format!("Live: {}", audio_analyzer.start_live(input, source)?)
+        }
+        Input::Pcm(config) => {
+            let (input, source) = open_pcm(config)?;
+            format!("PCM: {}", audio_analyzer.start_live(input, source)?)
+        }
+        Input::Signal(signal) => {
+            audio_analyzer.start_signal(signal.clone(), options.duration)?;
+            format!("Signal: {}", signal)
+        }
+    };
+    window.set_title(&window_title(&label));
+
+    let mut visualizer = Visualizer::new(
+        features,
+        window.get_framebuffer_size(),
+        options.seed,
+        options.bindings.clone(),
+        options.scene,
+    )?;
+    // Liste bitti ya da kalan parçaların hiçbiri açılamadı; bir tuşa basılana kadar beklenir
+    let mut idle = false;
 
     while !window.should_close() {
         glfw.poll_events();
+        let mut step = None;
         for (_, event) in glfw::flush_messages(&events) {
-            match event {
-                glfw::WindowEvent::Key(Key::Escape, _, Action::Press, _) => {
-                    window.set_should_close(true)
+            let glfw::WindowEvent::Key(key, _, action, _) = event else {
+                continue;
+            };
+            // Basılı tutulunca yalnızca atlama ve ses düzeyi tekrarlanır
+            if action == Action::Release {
+                continue;
+            }
+            if transport_key(&mut audio_analyzer, key) || action == Action::Repeat {
+                continue;
+            }
+            match key {
+                Key::Escape => window.set_should_close(true),
+                Key::V => eprintln!("scene: {}", visualizer.next_scene()),
+                Key::N | Key::P | Key::S | Key::R => {
+                    if let Some(playlist) = &mut playlist {
+                        step = playlist_key(playlist, key).or(step);
+                    }
+                }
+                Key::Space => {
+                    if let Some(paused) = audio_analyzer.toggle_pause() {
+                        eprintln!("{}", if paused { "paused" } else { "playing" });
+                    }
+                }
+                Key::A => {
+                    if let Some(start) = audio_analyzer.mark_loop_start() {
+                        eprintln!("loop start: {}", format_time(start));
+                    }
+                }
+                Key::B => match audio_analyzer.mark_loop_end() {
+                    Some((start, end)) => {
+                        eprintln!("loop: {} - {}", format_time(start), format_time(end))
+                    }
+                    None => eprintln!("loop end must come after a loop start (A)"),
+                },
+                Key::C => {
+                    audio_analyzer.clear_loop();
+                    eprintln!("loop cleared");
                 }
                 _ => {}
             }
         }
 
-        visualizer.render();
+        // Parça kendiliğinden bittiyse tekrar kipine göre sıradaki
+        if step.is_none() && !idle && audio_analyzer.is_finished() {
+            audio_analyzer.stop();
+            step = playlist
+                .as_mut()
+                .and_then(Playlist::advance)
+                .map(|_| Step::Forward);
+            idle = step.is_none();
+        }
+        if let (Some(step), Some(playlist)) = (step, &mut playlist) {
+            match play_current(&mut audio_analyzer, playlist, step) {
+                Ok(track) => {
+                    window.set_title(&window_title(&track.label(playlist.current())));
+                    idle = false;
+                }
+                Err(err) => {
+                    eprintln!("error: {}", err);
+                    idle = true;
+                }
+            }
+        }
+
+        visualizer.render(audio_analyzer.scope_tap());
         window.swap_buffers();
     }
+
+    Ok(())
 }
@@ -0,0 +1,158 @@
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use crate::bands::Band;
+
+// Uyarlanır eşik için tutulan akı geçmişi
+const HISTORY_SECONDS: f64 = 1.0;
+// Aynı bantta iki vuruş arasındaki en kısa süre
+const MIN_INTERVAL: f64 = 0.1;
+// Sessiz bölümlerde gürültünün vuruş sayılmaması için alt sınır
+const FLUX_FLOOR: f32 = 0.005;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BeatEvent {
+    // Parça başından itibaren saniye
+    pub time: f64,
+    // 0..1, eşiği ne kadar aştığı
+    pub strength: f32,
+    // `AnalyzerConfig::bands` indisi
+    pub band: usize,
+}
+
+struct BandTracker {
+    bins: Range<usize>,
+    history: VecDeque<f32>,
+    previous_flux: f32,
+    rising: bool,
+    last_onset: f64,
+}
+
+impl BandTracker {
+    // Bir önceki karenin akısı yerel tepe ve eşiğin üstündeyse vuruş döner
+    fn process(
+        &mut self,
+        flux: f32,
+        time: f64,
+        history_len: usize,
+        sensitivity: f32,
+    ) -> Option<f32> {
+        let candidate = self.previous_flux;
+        let mut onset = None;
+
+        if self.rising && candidate >= flux && self.history.len() >= history_len / 2 {
+            let count = self.history.len() as f32;
+            let mean = self.history.iter().sum::<f32>() / count;
+            let variance = self
+                .history
+                .iter()
+                .map(|value| (value - mean).powi(2))
+                .sum::<f32>()
+                / count;
+            let threshold = (mean + sensitivity * variance.sqrt()).max(FLUX_FLOOR);
+
+            if candidate > threshold && time - self.last_onset >= MIN_INTERVAL {
+                self.last_onset = time;
+                onset = Some((1.0 - threshold / candidate).clamp(0.0, 1.0));
+            }
+        }
+
+        self.history.push_back(candidate);
+        if self.history.len() > history_len {
+            self.history.pop_front();
+        }
+        self.rising = flux > candidate;
+        self.previous_flux = flux;
+
+        onset
+    }
+}
+
+// Bant başına spektral akı (log genlikteki artışların ortalaması) ve
+// ortalama + k * standart sapma biçiminde uyarlanır eşik
+pub struct OnsetDetector {
+    previous: Vec<f32>,
+    // Atlamadan sonraki ilk kare yalnızca `previous`'ı doldurur; iki konum arasındaki fark vuruş sayılmaz
+    resync: bool,
+    trackers: Vec<BandTracker>,
+    history_len: usize,
+    hop_seconds: f64,
+    sensitivity: f32,
+}
+
+impl OnsetDetector {
+    pub fn new(
+        bands: &[Band],
+        bin_hz: f32,
+        bin_count: usize,
+        hop_seconds: f64,
+        sensitivity: f32,
+    ) -> Self {
+        let trackers = bands
+            .iter()
+            .map(|band| BandTracker {
+                bins: band.bins(bin_hz, bin_count),
+                history: VecDeque::new(),
+                previous_flux: 0.0,
+                rising: false,
+                last_onset: f64::NEG_INFINITY,
+            })
+            .collect();
+
+        Self {
+            previous: vec![0.0; bin_count],
+            resync: false,
+            trackers,
+            history_len: ((HISTORY_SECONDS / hop_seconds).round() as usize).max(4),
+            hop_seconds,
+            sensitivity,
+        }
+    }
+
+    // Çalma konumu atladı; zaman geriye de gitmiş olabilir
+    pub fn seek(&mut self) {
+        self.resync = true;
+        for tracker in &mut self.trackers {
+            tracker.last_onset = f64::NEG_INFINITY;
+            tracker.rising = false;
+        }
+    }
+
+    // `spectrum` 0..1 normalize dB spektrumu, `time` pencere merkezinin zamanıdır.
+    // Tüm spektrumun akısını (tempo takibi için başlangıç zarfı) döndürür.
+    pub fn process(&mut self, spectrum: &[f32], time: f64, events: &mut Vec<BeatEvent>) -> f32 {
+        if std::mem::take(&mut self.resync) {
+            self.previous.copy_from_slice(spectrum);
+            return 0.0;
+        }
+
+        let rises: Vec<f32> = spectrum
+            .iter()
+            .zip(&self.previous)
+            .map(|(current, previous)| (current - previous).max(0.0))
+            .collect();
+        self.previous.copy_from_slice(spectrum);
+
+        // Tepe bir kare geriden tespit edildiği için olay zamanı da bir hop geridir
+        let onset_time = time - self.hop_seconds;
+        for (band, tracker) in self.trackers.iter_mut().enumerate() {
+            let bins = tracker.bins.clone();
+            let flux = if bins.is_empty() {
+                0.0
+            } else {
+                rises[bins.clone()].iter().sum::<f32>() / bins.len() as f32
+            };
+            if let Some(strength) =
+                tracker.process(flux, onset_time, self.history_len, self.sensitivity)
+            {
+                events.push(BeatEvent {
+                    time: onset_time,
+                    strength,
+                    band,
+                });
+            }
+        }
+
+        rises.iter().sum::<f32>() / rises.len().max(1) as f32
+    }
+}
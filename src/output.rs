@@ -0,0 +1,123 @@
+use rodio::{OutputStream, Source};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::error::VisualizerError;
+
+const NULL_TICK: Duration = Duration::from_millis(10);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputMode {
+    Device,
+    // Ses kartına gönderilmez, sadece saat hızında tüketilir
+    Null { speed: f32 },
+}
+
+pub enum AudioOutput {
+    Device { _stream: OutputStream },
+    Null { _sink: NullSink },
+}
+
+impl AudioOutput {
+    pub fn play<S>(mode: OutputMode, source: S) -> Result<Self, VisualizerError>
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        match mode {
+            OutputMode::Device => {
+                let (stream, stream_handle) =
+                    OutputStream::try_default().map_err(VisualizerError::NoOutputDevice)?;
+                let _ = stream_handle.play_raw(source);
+                Ok(AudioOutput::Device { _stream: stream })
+            }
+            OutputMode::Null { speed } => Ok(AudioOutput::Null {
+                _sink: NullSink::spawn(source, speed),
+            }),
+        }
+    }
+
+    // Canlı girişler çalınmaz; kaynak veri geldikçe hemen tüketilir
+    pub fn drain<S>(source: S) -> Self
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        AudioOutput::Null {
+            _sink: NullSink::drain(source),
+        }
+    }
+}
+
+pub struct NullSink {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl NullSink {
+    pub fn spawn<S>(mut source: S, speed: f32) -> Self
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+
+        let thread = thread::spawn(move || {
+            let mut last_tick = Instant::now();
+            let mut budget = 0.0f64;
+
+            while !thread_stop.load(Ordering::Relaxed) {
+                // Geçen süreye karşılık gelen örnek sayısı kadar kaynaktan çek
+                let now = Instant::now();
+                let rate = source.sample_rate() as f64 * source.channels() as f64;
+                budget += now.duration_since(last_tick).as_secs_f64() * rate * speed as f64;
+                last_tick = now;
+
+                while budget >= 1.0 {
+                    if source.next().is_none() {
+                        return;
+                    }
+                    budget -= 1.0;
+                }
+
+                thread::sleep(NULL_TICK);
+            }
+        });
+
+        Self {
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    // Hız sınırı yok: kaynak kendi hızında (örn. yakalama kartının) bekletir
+    pub fn drain<S>(mut source: S) -> Self
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+
+        let thread = thread::spawn(move || {
+            while !thread_stop.load(Ordering::Relaxed) {
+                if source.next().is_none() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for NullSink {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
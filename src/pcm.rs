@@ -0,0 +1,189 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::error::VisualizerError;
+use crate::live::{LiveFeed, LiveInput, LiveSource};
+
+// Okuyucu iş parçacığının her seferde istediği çerçeve sayısı (~10 ms)
+const READ_FRAMES: usize = 512;
+// Gerçek zamandan bu kadar önde olunursa beklenir; daha hızlı yazan üreticiler
+// (ör. `-re`siz ffmpeg) boru üzerinden yavaşlatılır
+const MAX_LEAD: Duration = Duration::from_millis(50);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PcmFormat {
+    U8,
+    S16Le,
+    S16Be,
+    S24Le,
+    S32Le,
+    F32Le,
+}
+
+impl PcmFormat {
+    fn sample_bytes(self) -> usize {
+        match self {
+            PcmFormat::U8 => 1,
+            PcmFormat::S16Le | PcmFormat::S16Be => 2,
+            PcmFormat::S24Le => 3,
+            PcmFormat::S32Le | PcmFormat::F32Le => 4,
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> f32 {
+        match self {
+            PcmFormat::U8 => (bytes[0] as f32 - 128.0) / 128.0,
+            PcmFormat::S16Le => i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32768.0,
+            PcmFormat::S16Be => i16::from_be_bytes([bytes[0], bytes[1]]) as f32 / 32768.0,
+            // İşaret biti en üst bayta taşınıp geri kaydırılır
+            PcmFormat::S24Le => {
+                (i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8) as f32 / 8_388_608.0
+            }
+            PcmFormat::S32Le => {
+                i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32
+                    / 2_147_483_648.0
+            }
+            PcmFormat::F32Le => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        }
+    }
+}
+
+impl FromStr for PcmFormat {
+    type Err = String;
+
+    // ffmpeg'in `-f` adları
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "u8" => Ok(PcmFormat::U8),
+            "s16le" => Ok(PcmFormat::S16Le),
+            "s16be" => Ok(PcmFormat::S16Be),
+            "s24le" => Ok(PcmFormat::S24Le),
+            "s32le" => Ok(PcmFormat::S32Le),
+            "f32le" => Ok(PcmFormat::F32Le),
+            _ => Err(format!(
+                "unknown PCM format '{}', expected u8, s16le, s16be, s24le, s32le or f32le",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PcmConfig {
+    // None standart girdidir
+    pub path: Option<PathBuf>,
+    pub format: PcmFormat,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+// MPD'nin FIFO çıkışının varsayılanı: 44100:16:2
+impl Default for PcmConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            format: PcmFormat::S16Le,
+            sample_rate: 44100,
+            channels: 2,
+        }
+    }
+}
+
+impl PcmConfig {
+    fn name(&self) -> String {
+        match &self.path {
+            Some(path) => path.display().to_string(),
+            None => "stdin".to_string(),
+        }
+    }
+}
+
+// Ham PCM'i arka planda okur. FIFO açılışı yazan taraf bağlanana kadar
+// beklediği için dosya da okuyucu iş parçacığında açılır.
+pub fn open_pcm(config: &PcmConfig) -> Result<(LiveInput, LiveSource), VisualizerError> {
+    // Olmayan bir yol hemen bildirilsin
+    if let Some(path) = &config.path {
+        std::fs::metadata(path).map_err(|err| VisualizerError::from_io(path.clone(), err))?;
+    }
+
+    let (source, feed) = LiveSource::new(config.channels, config.sample_rate);
+    let reader_feed = feed.clone();
+    let reader_config = config.clone();
+    thread::spawn(move || {
+        let name = reader_config.name();
+        if let Err(err) = read_pcm(&reader_config, &reader_feed) {
+            eprintln!("warning: reading PCM from {} failed: {}", name, err);
+        }
+        // Kaynak bitsin diye
+        reader_feed.stop();
+    });
+
+    Ok((LiveInput::new(feed, None, config.name()), source))
+}
+
+fn read_pcm(config: &PcmConfig, feed: &LiveFeed) -> io::Result<()> {
+    let mut reader: Box<dyn Read> = match &config.path {
+        Some(path) => Box::new(File::open(path)?),
+        None => Box::new(io::stdin().lock()),
+    };
+
+    let sample_bytes = config.format.sample_bytes();
+    let frame_bytes = sample_bytes * config.channels as usize;
+    let mut buffer = vec![0u8; READ_FRAMES * frame_bytes];
+    // Tamamlanmamış son çerçeve bir sonraki okumaya kalır
+    let mut filled = 0;
+    let mut frames_read = 0u64;
+    let mut started: Option<Instant> = None;
+
+    while !feed.is_stopped() {
+        let read = match reader.read(&mut buffer[filled..]) {
+            Ok(0) => return Ok(()),
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        filled += read;
+
+        let whole = filled - filled % frame_bytes;
+        if whole == 0 {
+            continue;
+        }
+        let samples = buffer[..whole]
+            .chunks_exact(sample_bytes)
+            .map(|bytes| config.format.decode(bytes));
+        if !feed.push(samples) {
+            return Ok(());
+        }
+        buffer.copy_within(whole..filled, 0);
+        filled -= whole;
+
+        // Saat ilk veriyle başlar; FIFO'da beklenen süre sayılmaz
+        frames_read += (whole / frame_bytes) as u64;
+        let started = *started.get_or_insert_with(Instant::now);
+        let due = Duration::from_secs_f64(frames_read as f64 / config.sample_rate as f64);
+        if let Some(lead) = due.checked_sub(started.elapsed() + MAX_LEAD) {
+            thread::sleep(lead);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_full_scale_samples() {
+        assert_eq!(PcmFormat::U8.decode(&[0]), -1.0);
+        assert_eq!(PcmFormat::S16Le.decode(&[0x00, 0x80]), -1.0);
+        assert_eq!(PcmFormat::S16Be.decode(&[0x40, 0x00]), 0.5);
+        assert_eq!(PcmFormat::S24Le.decode(&[0x00, 0x00, 0xc0]), -0.5);
+        assert_eq!(PcmFormat::S32Le.decode(&[0, 0, 0, 0x40]), 0.5);
+        assert_eq!(PcmFormat::F32Le.decode(&0.25f32.to_le_bytes()), 0.25);
+        assert!("s8".parse::<PcmFormat>().is_err());
+    }
+}
@@ -0,0 +1,213 @@
+use rodio::source::SeekError;
+use rodio::Source;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Analiz halkasına kaç çerçevede bir toplu yazılacağı
+const TAP_CHUNK: usize = 256;
+
+// Çalma tarafının kaynaktan kaç örnek çektiğini sayar
+pub struct PlaybackClock {
+    // Parça başından itibaren; atlamalarda hedefe ayarlanır
+    samples: AtomicU64,
+    channels: u16,
+    finished: AtomicBool,
+    seeks: AtomicU64,
+}
+
+impl PlaybackClock {
+    pub fn new(channels: u16) -> Self {
+        Self {
+            samples: AtomicU64::new(0),
+            channels: channels.max(1),
+            finished: AtomicBool::new(false),
+            seeks: AtomicU64::new(0),
+        }
+    }
+
+    pub fn frames(&self) -> u64 {
+        self.samples.load(Ordering::Relaxed) / self.channels as u64
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Relaxed)
+    }
+
+    // Her atlamada artar; analiz döngüsü sayacın değiştiğini görünce konumunu saate eşitler
+    pub fn seek_count(&self) -> u64 {
+        self.seeks.load(Ordering::Acquire)
+    }
+
+    // Parça sonuna gelinmeden durdurulunca analiz iş parçacıkları da çıksın
+    pub fn finish(&self) {
+        self.finished.store(true, Ordering::Relaxed);
+    }
+}
+
+// Son çalınan çerçevelerin sol/sağ kopyası; bellek kullanımı parça uzunluğundan bağımsızdır
+pub struct SampleRing {
+    buffer: Vec<[f32; 2]>,
+    // Mutlak çerçeve indisleri; [start, written) aralığının son `buffer.len()` kadarı geçerlidir
+    start: u64,
+    written: u64,
+}
+
+impl SampleRing {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buffer: vec![[0.0; 2]; capacity.max(1)],
+            start: 0,
+            written: 0,
+        }
+    }
+
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    pub fn push(&mut self, frames: &[[f32; 2]]) {
+        let capacity = self.buffer.len();
+        for &frame in frames {
+            self.buffer[(self.written % capacity as u64) as usize] = frame;
+            self.written += 1;
+        }
+    }
+
+    // Atlamadan sonra eski çerçeveler okunmaz; yazım `frame`'den devam eder
+    pub fn seek(&mut self, frame: u64) {
+        self.start = frame;
+        self.written = frame;
+    }
+
+    // `start` mutlak çerçeve indisidir; halkada olmayan çerçeveler sıfır okunur
+    pub fn read(&self, start: i64, left: &mut [f32], right: &mut [f32]) {
+        let capacity = self.buffer.len() as i64;
+        let oldest = (self.written as i64 - capacity).max(self.start as i64);
+        for (i, (left, right)) in left.iter_mut().zip(right.iter_mut()).enumerate() {
+            let index = start + i as i64;
+            [*left, *right] = if index >= oldest.max(0) && index < self.written as i64 {
+                self.buffer[(index % capacity) as usize]
+            } else {
+                [0.0; 2]
+            };
+        }
+    }
+}
+
+// Çalınan örnekleri olduğu gibi geçirir, aynı anda saati ilerletir ve analiz halkasını besler
+pub struct TeeSource<S> {
+    inner: S,
+    clock: Arc<PlaybackClock>,
+    ring: Arc<Mutex<SampleRing>>,
+    channels: usize,
+    // Çerçevenin ilk iki kanalı ve kalanların toplamı
+    frame: [f32; 3],
+    frame_fill: usize,
+    pending: Vec<[f32; 2]>,
+}
+
+impl<S> TeeSource<S>
+where
+    S: Source<Item = f32>,
+{
+    pub fn new(inner: S, clock: Arc<PlaybackClock>, ring: Arc<Mutex<SampleRing>>) -> Self {
+        let channels = inner.channels().max(1) as usize;
+        Self {
+            inner,
+            clock,
+            ring,
+            channels,
+            frame: [0.0; 3],
+            frame_fill: 0,
+            pending: Vec::with_capacity(TAP_CHUNK),
+        }
+    }
+
+    // Mono kaynakta iki kanal aynıdır. İkiden fazla kanalda ilk ikisi sol ve sağdır,
+    // kalanlar ikisine eşit dağıtılır; sol ve sağın ortalaması yine bütün kanalların
+    // ortalamasıdır.
+    fn stereo_frame(&self) -> [f32; 2] {
+        let [left, right, rest] = self.frame;
+        if self.channels == 1 {
+            return [left, left];
+        }
+        let scale = 2.0 / self.channels as f32;
+        [(left + rest * 0.5) * scale, (right + rest * 0.5) * scale]
+    }
+
+    fn flush(&mut self) {
+        if !self.pending.is_empty() {
+            self.ring.lock().unwrap().push(&self.pending);
+            self.pending.clear();
+        }
+    }
+}
+
+impl<S> Iterator for TeeSource<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        match self.inner.next() {
+            Some(sample) => {
+                self.frame[self.frame_fill.min(2)] += sample;
+                self.frame_fill += 1;
+                if self.frame_fill == self.channels {
+                    self.pending.push(self.stereo_frame());
+                    self.frame = [0.0; 3];
+                    self.frame_fill = 0;
+                    if self.pending.len() >= TAP_CHUNK {
+                        self.flush();
+                    }
+                }
+                self.clock.samples.fetch_add(1, Ordering::Relaxed);
+                Some(sample)
+            }
+            None => {
+                self.flush();
+                self.clock.finished.store(true, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+}
+
+impl<S> Source for TeeSource<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    // Saat ve halka atlanan konuma taşınır; yarım kalan çerçeve atılır
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.flush();
+        self.inner.try_seek(pos)?;
+
+        let frame = (pos.as_secs_f64() * self.inner.sample_rate() as f64) as u64;
+        self.frame = [0.0; 3];
+        self.frame_fill = 0;
+        self.ring.lock().unwrap().seek(frame);
+        self.clock
+            .samples
+            .store(frame * self.channels as u64, Ordering::Relaxed);
+        self.clock.seeks.fetch_add(1, Ordering::Release);
+        Ok(())
+    }
+}
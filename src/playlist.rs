@@ -0,0 +1,326 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::error::VisualizerError;
+
+// Dizinlerde yalnızca bu uzantılar parça sayılır
+const AUDIO_EXTENSIONS: &[&str] = &[
+    "mp3", "flac", "wav", "wave", "aif", "aiff", "ogg", "oga", "m4a", "mp4", "aac", "mka", "webm",
+    "caf",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RepeatMode {
+    Off,
+    All,
+    One,
+}
+
+impl RepeatMode {
+    pub fn next(self) -> Self {
+        match self {
+            RepeatMode::Off => RepeatMode::All,
+            RepeatMode::All => RepeatMode::One,
+            RepeatMode::One => RepeatMode::Off,
+        }
+    }
+}
+
+impl fmt::Display for RepeatMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RepeatMode::Off => "off",
+            RepeatMode::All => "all",
+            RepeatMode::One => "one",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for RepeatMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Ok(RepeatMode::Off),
+            "all" => Ok(RepeatMode::All),
+            "one" => Ok(RepeatMode::One),
+            _ => Err(format!(
+                "unknown repeat mode '{}', expected off, all or one",
+                s
+            )),
+        }
+    }
+}
+
+pub struct Playlist {
+    tracks: Vec<PathBuf>,
+    // Çalma sırası; karıştırma kapalıyken 0..n
+    order: Vec<usize>,
+    // `order` içindeki konum
+    position: usize,
+    shuffle: bool,
+    repeat: RepeatMode,
+    rng: StdRng,
+}
+
+impl Playlist {
+    pub fn new(tracks: Vec<PathBuf>) -> Self {
+        Self {
+            order: (0..tracks.len()).collect(),
+            tracks,
+            position: 0,
+            shuffle: false,
+            repeat: RepeatMode::Off,
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    // Komut satırındaki girdiler sırasıyla açılır: dosyalar olduğu gibi, dizinler
+    // alfabetik ve özyinelemeli, .m3u/.m3u8/.pls listeleri içerdikleri yollarla
+    pub fn from_paths(paths: &[PathBuf]) -> Result<Self, VisualizerError> {
+        let mut tracks = Vec::new();
+        for path in paths {
+            if path.is_dir() {
+                collect_directory(path, &mut tracks)?;
+            } else if is_playlist_file(path) {
+                let text = fs::read_to_string(path)
+                    .map_err(|err| VisualizerError::from_io(path.clone(), err))?;
+                let base = path.parent().unwrap_or(Path::new(""));
+                let entries = if has_extension(path, &["pls"]) {
+                    parse_pls(&text)
+                } else {
+                    parse_m3u(&text)
+                };
+                tracks.extend(entries.into_iter().map(|entry| base.join(entry)));
+            } else {
+                tracks.push(path.clone());
+            }
+        }
+
+        if tracks.is_empty() {
+            return Err(VisualizerError::EmptyPlaylist);
+        }
+        Ok(Self::new(tracks))
+    }
+
+    pub fn track_count(&self) -> usize {
+        self.tracks.len()
+    }
+
+    pub fn current(&self) -> &Path {
+        &self.tracks[self.order[self.position]]
+    }
+
+    pub fn shuffle(&self) -> bool {
+        self.shuffle
+    }
+
+    pub fn repeat(&self) -> RepeatMode {
+        self.repeat
+    }
+
+    pub fn set_repeat(&mut self, repeat: RepeatMode) {
+        self.repeat = repeat;
+    }
+
+    // Parça kendiliğinden bittiğinde çalınacak olan; liste bittiyse None
+    pub fn advance(&mut self) -> Option<&Path> {
+        if self.repeat == RepeatMode::One {
+            return Some(self.current());
+        }
+        self.skip_forward()
+    }
+
+    // Kullanıcının istediği sonraki parça; tekrar kipi "one" olsa da ilerler
+    pub fn skip_forward(&mut self) -> Option<&Path> {
+        if self.position + 1 < self.order.len() {
+            self.position += 1;
+        } else if self.repeat == RepeatMode::Off {
+            return None;
+        } else {
+            // Her turda yeni bir karışık sıra
+            if self.shuffle {
+                self.order.shuffle(&mut self.rng);
+            }
+            self.position = 0;
+        }
+        Some(self.current())
+    }
+
+    // Listenin başında, tekrar kapalıyken aynı parça baştan çalınır
+    pub fn skip_back(&mut self) -> &Path {
+        if self.position > 0 {
+            self.position -= 1;
+        } else if self.repeat != RepeatMode::Off {
+            self.position = self.order.len() - 1;
+        }
+        self.current()
+    }
+
+    // Henüz hiçbir şey çalmadan karıştırma: ilk parça da rastgele seçilir
+    pub fn start_shuffled(&mut self) {
+        self.shuffle = true;
+        self.order.shuffle(&mut self.rng);
+        self.position = 0;
+    }
+
+    // Çalan parça yerinde kalır; karıştırılan yalnızca ondan sonrakilerdir
+    pub fn set_shuffle(&mut self, shuffle: bool) {
+        let current = self.order[self.position];
+        self.shuffle = shuffle;
+        self.order = (0..self.tracks.len()).collect();
+        if shuffle {
+            self.order.retain(|&index| index != current);
+            self.order.shuffle(&mut self.rng);
+            self.order.insert(0, current);
+            self.position = 0;
+        } else {
+            self.position = current;
+        }
+    }
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            extensions
+                .iter()
+                .any(|known| extension.eq_ignore_ascii_case(known))
+        })
+}
+
+fn is_playlist_file(path: &Path) -> bool {
+    has_extension(path, &["m3u", "m3u8", "pls"])
+}
+
+fn collect_directory(dir: &Path, tracks: &mut Vec<PathBuf>) -> Result<(), VisualizerError> {
+    let entries =
+        fs::read_dir(dir).map_err(|err| VisualizerError::from_io(dir.to_path_buf(), err))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        if path.is_dir() {
+            collect_directory(&path, tracks)?;
+        } else if has_extension(&path, AUDIO_EXTENSIONS) {
+            tracks.push(path);
+        }
+    }
+    Ok(())
+}
+
+// Akış adresleri çalınamadığı için atlanır
+fn is_stream_url(entry: &str) -> bool {
+    entry.contains("://") && !entry.starts_with("file://")
+}
+
+fn playlist_entry(entry: &str) -> Option<PathBuf> {
+    let entry = entry.trim();
+    if entry.is_empty() || is_stream_url(entry) {
+        return None;
+    }
+    Some(PathBuf::from(
+        entry.strip_prefix("file://").unwrap_or(entry),
+    ))
+}
+
+// '#' ile başlayan satırlar (#EXTM3U, #EXTINF, ...) yorumdur
+fn parse_m3u(text: &str) -> Vec<PathBuf> {
+    text.lines()
+        .map(|line| line.trim_start_matches('\u{feff}'))
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(playlist_entry)
+        .collect()
+}
+
+// "FileN=yol" girdileri N sırasıyla
+fn parse_pls(text: &str) -> Vec<PathBuf> {
+    let mut entries: Vec<(u32, PathBuf)> = text
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.trim().split_once('=')?;
+            let number = key.trim().strip_prefix("File")?.parse().ok()?;
+            Some((number, playlist_entry(value)?))
+        })
+        .collect();
+    entries.sort_by_key(|(number, _)| *number);
+    entries.into_iter().map(|(_, path)| path).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playlist(count: usize) -> Playlist {
+        Playlist::new(
+            (0..count)
+                .map(|i| PathBuf::from(format!("{}.mp3", i)))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn m3u_skips_comments_and_streams() {
+        let text = "\u{feff}#EXTM3U\n#EXTINF:123,Artist - Title\nmusic/a.mp3\n\n\
+                    http://radio.example/stream\nfile:///abs/b.flac\r\n";
+        assert_eq!(
+            parse_m3u(text),
+            vec![PathBuf::from("music/a.mp3"), PathBuf::from("/abs/b.flac")]
+        );
+    }
+
+    #[test]
+    fn pls_orders_by_entry_number() {
+        let text = "[playlist]\nFile2=b.ogg\nTitle2=B\nFile1=a.ogg\nNumberOfEntries=2\nVersion=2\n";
+        assert_eq!(
+            parse_pls(text),
+            vec![PathBuf::from("a.ogg"), PathBuf::from("b.ogg")]
+        );
+    }
+
+    #[test]
+    fn advance_follows_repeat_mode() {
+        let mut list = playlist(2);
+        assert_eq!(list.advance(), Some(Path::new("1.mp3")));
+        assert_eq!(list.advance(), None);
+
+        list.set_repeat(RepeatMode::All);
+        assert_eq!(list.advance(), Some(Path::new("0.mp3")));
+
+        list.set_repeat(RepeatMode::One);
+        assert_eq!(list.advance(), Some(Path::new("0.mp3")));
+        assert_eq!(list.skip_forward(), Some(Path::new("1.mp3")));
+        assert_eq!(list.skip_back(), Path::new("0.mp3"));
+        assert_eq!(list.skip_back(), Path::new("1.mp3"));
+    }
+
+    #[test]
+    fn shuffle_keeps_current_track_and_visits_all() {
+        let mut list = playlist(8);
+        list.skip_forward();
+        list.set_shuffle(true);
+        assert_eq!(list.current(), Path::new("1.mp3"));
+
+        let mut seen = vec![list.current().to_path_buf()];
+        while let Some(path) = list.skip_forward() {
+            seen.push(path.to_path_buf());
+        }
+        seen.sort();
+        let mut all: Vec<PathBuf> = list.tracks.clone();
+        all.sort();
+        assert_eq!(seen, all);
+
+        list.set_shuffle(false);
+        let current = list.current().to_path_buf();
+        assert_eq!(list.tracks[list.position], current);
+    }
+}
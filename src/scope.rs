@@ -0,0 +1,390 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use crate::error::VisualizerError;
+use crate::playback::{PlaybackClock, SampleRing};
+use crate::shaders::{
+    ShaderProgram, PHOSPHOR_FADE_SHADER, PHOSPHOR_PRESENT_SHADER, PHOSPHOR_VERTEX_SHADER,
+    SCOPE_FRAGMENT_SHADER, SCOPE_VERTEX_SHADER,
+};
+
+// Bir video karesinde çizilen en fazla çerçeve; uzun bir aradan sonra iz,
+// halkanın tamamı yerine yalnızca bu kadarından çizilir
+const SCOPE_MAX_FRAMES: usize = 4096;
+// Fosforun her karede koruduğu parlaklık
+const PERSISTENCE: f32 = 0.85;
+// Işının bir piksel yol başına bıraktığı parlaklık; yavaş hareket eden ışın daha parlaktır
+const BEAM_GAIN: f32 = 0.25;
+// Fosfor yeşili
+const BEAM_COLOR: [f32; 3] = [0.3, 1.0, 0.45];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scene {
+    // Spektrumla sürülen şekil tüneli
+    Tunnel,
+    // Sol/sağ çiftlerinin Lissajous çizimi; mono dikey bir çizgidir
+    Vectorscope,
+}
+
+impl Scene {
+    pub fn next(self) -> Self {
+        match self {
+            Scene::Tunnel => Scene::Vectorscope,
+            Scene::Vectorscope => Scene::Tunnel,
+        }
+    }
+}
+
+impl fmt::Display for Scene {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Scene::Tunnel => "tunnel",
+            Scene::Vectorscope => "vectorscope",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Scene {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "tunnel" => Ok(Scene::Tunnel),
+            "vectorscope" | "goniometer" => Ok(Scene::Vectorscope),
+            _ => Err(format!(
+                "unknown scene '{}', expected tunnel or vectorscope",
+                s
+            )),
+        }
+    }
+}
+
+// FFT iş parçacığının okuduğu halkadan, dinleyicinin duyduğu yeni çerçeveleri okur
+pub struct ScopeTap {
+    clock: Arc<PlaybackClock>,
+    ring: Arc<Mutex<SampleRing>>,
+    latency_frames: i64,
+    next_frame: i64,
+    seeks: u64,
+}
+
+impl ScopeTap {
+    pub fn new(
+        clock: Arc<PlaybackClock>,
+        ring: Arc<Mutex<SampleRing>>,
+        latency_frames: i64,
+    ) -> Self {
+        let seeks = clock.seek_count();
+        Self {
+            clock,
+            ring,
+            latency_frames,
+            next_frame: 0,
+            seeks,
+        }
+    }
+
+    // Son çağrıdan beri duyulan çerçeveler, en fazla `SCOPE_MAX_FRAMES`;
+    // duraklatılınca saat ilerlemediği için boştur
+    pub fn read(&mut self, left: &mut Vec<f32>, right: &mut Vec<f32>) {
+        let seek_count = self.clock.seek_count();
+        let ring = self.ring.lock().unwrap();
+        // Henüz çekilmemiş örnekler okunmaz; negatif gecikmede iz halkanın başında kalır
+        let heard = (self.clock.frames() as i64 - self.latency_frames).min(ring.written() as i64);
+        if seek_count != self.seeks {
+            self.seeks = seek_count;
+            self.next_frame = heard;
+        }
+        let start = self.next_frame.max(heard - SCOPE_MAX_FRAMES as i64);
+        let count = (heard - start).max(0) as usize;
+        left.resize(count, 0.0);
+        right.resize(count, 0.0);
+        ring.read(start, left, right);
+        self.next_frame = self.next_frame.max(heard);
+    }
+}
+
+// Fosfor ekranlı osiloskop gibi çizen goniometre. Işın kayan noktalı bir dokuya
+// toplanır, doku her karede söner ve ekrana ızgarayla birlikte çizilir.
+pub struct Vectorscope {
+    beam_program: ShaderProgram,
+    fade_program: ShaderProgram,
+    present_program: ShaderProgram,
+    beam_vao: u32,
+    beam_vbo: u32,
+    quad_vao: u32,
+    quad_vbo: u32,
+    framebuffer: u32,
+    texture: u32,
+    // Piksel olarak çizim alanı
+    width: i32,
+    height: i32,
+    left: Vec<f32>,
+    right: Vec<f32>,
+    // Nokta başına x, y, parlaklık
+    vertices: Vec<f32>,
+    // Önceki karenin son noktası; iz kareler arasında kopmasın
+    last_point: Option<[f32; 2]>,
+}
+
+impl Vectorscope {
+    pub fn new(width: i32, height: i32) -> Result<Self, VisualizerError> {
+        let beam_program = ShaderProgram::new(SCOPE_VERTEX_SHADER, SCOPE_FRAGMENT_SHADER)?;
+        let fade_program = ShaderProgram::new(PHOSPHOR_VERTEX_SHADER, PHOSPHOR_FADE_SHADER)?;
+        let present_program = ShaderProgram::new(PHOSPHOR_VERTEX_SHADER, PHOSPHOR_PRESENT_SHADER)?;
+        let (width, height) = (width.max(1), height.max(1));
+
+        unsafe {
+            let (mut beam_vao, mut beam_vbo) = (0, 0);
+            gl::GenVertexArrays(1, &mut beam_vao);
+            gl::GenBuffers(1, &mut beam_vbo);
+            gl::BindVertexArray(beam_vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, beam_vbo);
+            let stride = (3 * std::mem::size_of::<f32>()) as i32;
+            gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, stride, std::ptr::null());
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(
+                1,
+                1,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                (2 * std::mem::size_of::<f32>()) as *const _,
+            );
+            gl::EnableVertexAttribArray(1);
+
+            // Üçgen şeridi olarak tam ekran dörtgen
+            let quad: [f32; 8] = [-1.0, -1.0, 1.0, -1.0, -1.0, 1.0, 1.0, 1.0];
+            let (mut quad_vao, mut quad_vbo) = (0, 0);
+            gl::GenVertexArrays(1, &mut quad_vao);
+            gl::GenBuffers(1, &mut quad_vbo);
+            gl::BindVertexArray(quad_vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, quad_vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (quad.len() * std::mem::size_of::<f32>()) as isize,
+                quad.as_ptr() as *const _,
+                gl::STATIC_DRAW,
+            );
+            gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 0, std::ptr::null());
+            gl::EnableVertexAttribArray(0);
+
+            // 8 bitlik bir dokuda söndürme küçük değerlerde takılır ve iz hiç kaybolmaz
+            let mut texture = 0;
+            gl::GenTextures(1, &mut texture);
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA16F as i32,
+                width,
+                height,
+                0,
+                gl::RGBA,
+                gl::FLOAT,
+                std::ptr::null(),
+            );
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+
+            let mut framebuffer = 0;
+            gl::GenFramebuffers(1, &mut framebuffer);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                texture,
+                0,
+            );
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+
+            let scope = Self {
+                beam_program,
+                fade_program,
+                present_program,
+                beam_vao,
+                beam_vbo,
+                quad_vao,
+                quad_vbo,
+                framebuffer,
+                texture,
+                width,
+                height,
+                left: Vec::with_capacity(SCOPE_MAX_FRAMES),
+                right: Vec::with_capacity(SCOPE_MAX_FRAMES),
+                vertices: Vec::with_capacity((SCOPE_MAX_FRAMES + 1) * 3),
+                last_point: None,
+            };
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                return Err(VisualizerError::GlInit(format!(
+                    "vectorscope framebuffer is incomplete (status {:#x})",
+                    status
+                )));
+            }
+            scope.reset();
+            Ok(scope)
+        }
+    }
+
+    // Fosforu temizler; sahneye dönüldüğünde eski iz görünmesin
+    pub fn reset(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+            gl::ClearColor(0.0, 0.0, 0.0, 0.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+
+    // `flash` ışını beyaza çeker. Çizimden sonra tünelin beklediği GL durumu geri yüklenir.
+    pub fn render(&mut self, tap: Option<&mut ScopeTap>, flash: f32) {
+        match tap {
+            Some(tap) => tap.read(&mut self.left, &mut self.right),
+            None => {
+                self.left.clear();
+                self.right.clear();
+            }
+        }
+        self.build_beam();
+
+        let aspect = self.width as f32 / self.height as f32;
+        let beam = BEAM_COLOR.map(|c| c + (1.0 - c) * flash.clamp(0.0, 1.0));
+        let beam_color = nalgebra_glm::vec4(beam[0], beam[1], beam[2], 1.0);
+        let grid_color = nalgebra_glm::vec4(BEAM_COLOR[0], BEAM_COLOR[1], BEAM_COLOR[2], 1.0);
+
+        unsafe {
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Enable(gl::BLEND);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            self.fade_program.use_program();
+            self.fade_program.set_float("fade", 1.0 - PERSISTENCE);
+            gl::BindVertexArray(self.quad_vao);
+            gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+
+            let points = self.vertices.len() / 3;
+            if points > 1 {
+                gl::BlendFunc(gl::ONE, gl::ONE);
+                self.beam_program.use_program();
+                self.beam_program.set_float("aspect", aspect);
+                self.beam_program.set_vec4("color", &beam_color);
+                gl::BindVertexArray(self.beam_vao);
+                gl::BindBuffer(gl::ARRAY_BUFFER, self.beam_vbo);
+                gl::BufferData(
+                    gl::ARRAY_BUFFER,
+                    (self.vertices.len() * std::mem::size_of::<f32>()) as isize,
+                    self.vertices.as_ptr() as *const _,
+                    gl::STREAM_DRAW,
+                );
+                gl::DrawArrays(gl::LINE_STRIP, 0, points as i32);
+            }
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            gl::Disable(gl::BLEND);
+            gl::ClearColor(0.0, 0.0, 0.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+            self.present_program.use_program();
+            self.present_program.set_int("phosphor", 0);
+            self.present_program.set_float("aspect", aspect);
+            self.present_program.set_vec4("color", &grid_color);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, self.texture);
+            gl::BindVertexArray(self.quad_vao);
+            gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+
+            gl::Enable(gl::DEPTH_TEST);
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+        }
+    }
+
+    // Goniometre ekseni: yukarı orta (L+R)/2, sağa yan (R-L)/2; yalnızca sol kanal
+    // sol üst köşegene, yalnızca sağ kanal sağ üst köşegene düşer
+    fn build_beam(&mut self) {
+        self.vertices.clear();
+        if self.left.is_empty() {
+            // Yeni çerçeve yoksa iz kopar; duraklatınca fosfor yavaşça söner
+            self.last_point = None;
+            return;
+        }
+
+        // Bir birimlik hareketin kısa kenarda kaç piksel olduğu
+        let pixels_per_unit = self.width.min(self.height) as f32 * 0.45;
+        let points = self
+            .left
+            .iter()
+            .zip(&self.right)
+            .map(|(&l, &r)| [(r - l) * 0.5, (l + r) * 0.5]);
+        let mut previous = self.last_point;
+        for point in self.last_point.into_iter().chain(points) {
+            let travel = previous.map_or(0.0, |[x, y]| {
+                (point[0] - x).hypot(point[1] - y) * pixels_per_unit
+            });
+            let intensity = BEAM_GAIN / travel.max(1.0);
+            self.vertices
+                .extend_from_slice(&[point[0], point[1], intensity]);
+            previous = Some(point);
+        }
+        self.last_point = previous;
+    }
+}
+
+impl Drop for Vectorscope {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.framebuffer);
+            gl::DeleteTextures(1, &self.texture);
+            gl::DeleteVertexArrays(1, &self.beam_vao);
+            gl::DeleteBuffers(1, &self.beam_vbo);
+            gl::DeleteVertexArrays(1, &self.quad_vao);
+            gl::DeleteBuffers(1, &self.quad_vbo);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::playback::TeeSource;
+    use rodio::buffer::SamplesBuffer;
+
+    #[test]
+    fn tap_reads_only_newly_heard_frames() {
+        let samples: Vec<f32> = (0..10240).flat_map(|i| [i as f32, -(i as f32)]).collect();
+        let clock = Arc::new(PlaybackClock::new(2));
+        let ring = Arc::new(Mutex::new(SampleRing::new(1 << 14)));
+        let mut tee = TeeSource::new(
+            SamplesBuffer::new(2, 44100, samples),
+            clock.clone(),
+            ring.clone(),
+        );
+        let mut tap = ScopeTap::new(clock, ring, 0);
+        let (mut left, mut right) = (Vec::new(), Vec::new());
+        let mut play = |frames: usize| tee.by_ref().take(frames * 2).count();
+
+        tap.read(&mut left, &mut right);
+        assert!(left.is_empty());
+
+        play(512);
+        tap.read(&mut left, &mut right);
+        assert_eq!(left, (0..512).map(|i| i as f32).collect::<Vec<_>>());
+        assert_eq!(right[511], -511.0);
+
+        play(256);
+        tap.read(&mut left, &mut right);
+        assert_eq!((left.len(), left[0]), (256, 512.0));
+
+        // Uzun bir aradan sonra yalnızca son çerçeveler çizilir
+        play(8192);
+        tap.read(&mut left, &mut right);
+        assert_eq!(left.len(), SCOPE_MAX_FRAMES);
+        assert_eq!(*left.last().unwrap(), 8959.0);
+    }
+}
@@ -1,13 +1,15 @@
 use std::ffi::CString;
 
+use crate::error::VisualizerError;
+
 pub struct ShaderProgram {
     id: u32,
 }
 
 impl ShaderProgram {
-    pub fn new(vertex_source: &str, fragment_source: &str) -> Result<Self, String> {
-        let vertex_shader = compile_shader(vertex_source, gl::VERTEX_SHADER)?;
-        let fragment_shader = compile_shader(fragment_source, gl::FRAGMENT_SHADER)?;
+    pub fn new(vertex_source: &str, fragment_source: &str) -> Result<Self, VisualizerError> {
+        let vertex_shader = compile_shader(vertex_source, gl::VERTEX_SHADER, "vertex")?;
+        let fragment_shader = compile_shader(fragment_source, gl::FRAGMENT_SHADER, "fragment")?;
 
         unsafe {
             let program = gl::CreateProgram();
@@ -29,7 +31,9 @@ impl ShaderProgram {
                     info_log.as_mut_ptr() as *mut i8,
                 );
                 info_log.set_len(len as usize);
-                return Err(String::from_utf8_lossy(&info_log).to_string());
+                return Err(VisualizerError::ShaderLink(
+                    String::from_utf8_lossy(&info_log).to_string(),
+                ));
             }
 
             gl::DeleteShader(vertex_shader);
@@ -61,6 +65,22 @@ impl ShaderProgram {
         }
     }
 
+    pub fn set_float_array(&self, name: &str, values: &[f32]) {
+        unsafe {
+            let name = CString::new(name).unwrap();
+            let location = gl::GetUniformLocation(self.id, name.as_ptr());
+            gl::Uniform1fv(location, values.len() as i32, values.as_ptr());
+        }
+    }
+
+    pub fn set_int(&self, name: &str, value: i32) {
+        unsafe {
+            let name = CString::new(name).unwrap();
+            let location = gl::GetUniformLocation(self.id, name.as_ptr());
+            gl::Uniform1i(location, value);
+        }
+    }
+
     pub fn set_float(&self, name: &str, value: f32) {
         unsafe {
             let name = CString::new(name).unwrap();
@@ -70,7 +90,11 @@ impl ShaderProgram {
     }
 }
 
-fn compile_shader(source: &str, shader_type: u32) -> Result<u32, String> {
+fn compile_shader(
+    source: &str,
+    shader_type: u32,
+    stage: &'static str,
+) -> Result<u32, VisualizerError> {
     unsafe {
         let shader = gl::CreateShader(shader_type);
         let c_str = CString::new(source.as_bytes()).unwrap();
@@ -90,7 +114,10 @@ fn compile_shader(source: &str, shader_type: u32) -> Result<u32, String> {
                 info_log.as_mut_ptr() as *mut i8,
             );
             info_log.set_len(len as usize);
-            return Err(String::from_utf8_lossy(&info_log).to_string());
+            return Err(VisualizerError::ShaderCompile {
+                stage,
+                log: String::from_utf8_lossy(&info_log).to_string(),
+            });
         }
 
         Ok(shader)
@@ -109,12 +136,19 @@ pub const VERTEX_SHADER: &str = r#"
     uniform float bassEnergy;
     uniform float midEnergy;
     uniform float highEnergy;
+    uniform float bands[16];
+    uniform int bandCount;
+    uniform float beatPhase;
+    uniform float tempoConfidence;
+    uniform float stereoMid;
+    uniform float stereoWidth;
     
     out vec3 FragPos;
     out vec2 TexCoord;
     out float Energy;
     out vec3 Normal;
     out float VertexGlow;
+    out float ViewX;
     
     // Dalga fonksiyonu
     float wave(vec3 pos, float freq, float amp) {
@@ -146,17 +180,30 @@ pub const VERTEX_SHADER: &str = r#"
         );
         pos.xz = rotation * pos.xz;
         
-        // Nabız efekti
-        float pulse = sin(time * (2.0 + bassEnergy * 3.0)) * 0.5 + 0.5;
+        // Nabız efekti; tempo güvenilirse vuruşa kilitlenir
+        float freePulse = sin(time * (2.0 + bassEnergy * 3.0)) * 0.5 + 0.5;
+        float beatPulse = pow(1.0 - beatPhase, 2.0);
+        float pulse = mix(freePulse, beatPulse, tempoConfidence);
         pos *= 1.0 + pulse * audioEnergy * 0.3;
         
+        // Bant dalgalanması: her köşe konumuna göre bir banda düşer
+        if (bandCount > 0) {
+            int band = int(mod(floor((aPos.x + aPos.y + aPos.z + 1.5) * 3.0), float(bandCount)));
+            pos += normalize(aPos) * bands[band] * 0.1;
+        }
+        
+        // Geniş stereoda şekiller yatay olarak gerilir
+        pos.x *= 1.0 + stereoWidth * 0.4;
+        
         // Vertex parlaklığı
-        VertexGlow = pulse * (1.0 - length(pos) * 0.5) + highEnergy * 0.5;
+        VertexGlow = pulse * (1.0 - length(pos) * 0.5) + highEnergy * 0.5 + stereoMid * 0.2;
         
         FragPos = vec3(model * vec4(pos, 1.0));
         TexCoord = pos.xy * 0.5 + 0.5;
         Energy = audioEnergy;
         Normal = normalize(pos);
+        // Kameranın solunda negatif; parçanın hangi kanala düştüğünü seçer
+        ViewX = (view * model * vec4(pos, 1.0)).x;
         
         gl_Position = projection * view * model * vec4(pos, 1.0);
     }
@@ -171,12 +218,30 @@ pub const FRAGMENT_SHADER: &str = r#"
     in float Energy;
     in vec3 Normal;
     in float VertexGlow;
+    in float ViewX;
     
     uniform vec4 color;
     uniform float time;
+    uniform float flash;
+    uniform float bpm;
+    uniform float barPhase;
+    uniform float tempoConfidence;
     uniform float bassEnergy;
     uniform float midEnergy;
     uniform float highEnergy;
+    uniform float bands[16];
+    uniform int bandCount;
+    uniform float stereoSide;
+    uniform float stereoBalance;
+    uniform float bandPan[16];
+    uniform float spectrumLeft[32];
+    uniform float spectrumRight[32];
+    uniform float chroma[12];
+    uniform float keyMinor;
+    uniform float tonality;
+    
+    // Baskın perde sınıfının tonu; main() başında kromadan hesaplanır
+    float PitchHue = 0.0;
     
     // Kaleidoskop efekti
     vec2 kaleidoscope(vec2 uv, float segments) {
@@ -191,18 +256,33 @@ pub const FRAGMENT_SHADER: &str = r#"
         return fract(sin(dot(p, vec2(12.9898, 78.233))) * 43758.5453);
     }
     
-    // Rainbow renk
+    // Rainbow renk; tonal müzikte renkler baskın perdenin tonu etrafında toplanır,
+    // minör tonda palet koyulaşır ve soğur
     vec3 rainbow(float t) {
-        vec3 c = 0.5 + 0.5 * cos(6.28318 * (t + vec3(0.0, 0.33, 0.67)));
-        return mix(c, vec3(1.0), 0.2);
+        float hue = PitchHue * tonality + t * mix(1.0, 0.3, tonality);
+        vec3 c = 0.5 + 0.5 * cos(6.28318 * (hue + vec3(0.0, 0.33, 0.67)));
+        c = mix(c, c * vec3(0.7, 0.75, 1.0), keyMinor);
+        return mix(c, vec3(1.0), 0.2 * (1.0 - keyMinor)) * (1.0 - keyMinor * 0.3);
+    }
+    
+    // Perde sınıfları beşliler çemberine dizilir ki yakın tonlar yakın renkler alsın;
+    // dördüncü kuvvet ortalamayı en güçlü perdeye çeker
+    float pitchHue() {
+        vec2 pitch = vec2(0.0);
+        for (int i = 0; i < 12; i++) {
+            float angle = 6.28318 * float(i * 7 % 12) / 12.0;
+            pitch += vec2(cos(angle), sin(angle)) * pow(chroma[i], 4.0);
+        }
+        return length(pitch) > 0.0 ? atan(pitch.y, pitch.x) / 6.28318 : 0.0;
     }
     
     void main() {
+        PitchHue = pitchHue();
         vec2 uv = TexCoord * 2.0 - 1.0;
         vec3 finalColor = color.rgb;
         
-        // Zaman bazlı renk kayması
-        float timeShift = time * 0.5;
+        // Zaman bazlı renk kayması; tempo güvenilirse her ölçüde bir tur döner
+        float timeShift = time * 0.5 + barPhase * tempoConfidence;
         vec3 shiftedColor = rainbow(timeShift + length(uv) * 0.2);
         
         // Kaleidoskop efekti
@@ -235,15 +315,38 @@ pub const FRAGMENT_SHADER: &str = r#"
         finalColor += neonColor * neonGlow * 0.5;
         finalColor += rainbow(fractal + timeShift) * highEnergy * 0.3;
         
+        // Ekranın iki yarısı: solda sol kanal, sağda sağ kanal
+        float screenSide = ViewX < 0.0 ? -1.0 : 1.0;
+        
+        // Bant halkaları: merkezden dışa doğru her halka bir bandı gösterir;
+        // bant hangi yana kaydıysa o yarıda daha parlaktır
+        if (bandCount > 0) {
+            int ring = int(min(length(uv), 0.999) * float(bandCount));
+            float panGain = 1.0 + bandPan[ring] * screenSide * 0.5;
+            finalColor += rainbow(float(ring) / float(bandCount) + timeShift) * bands[ring] * panGain * 0.15;
+        }
+        
+        // Kanal spektrumu parıltısı; yan sinyal güçlendikçe belirginleşir
+        int slot = int(clamp(TexCoord.y, 0.0, 0.999) * 32.0);
+        float channelLevel = screenSide < 0.0 ? spectrumLeft[slot] : spectrumRight[slot];
+        finalColor += rainbow(float(slot) / 32.0 + timeShift) * channelLevel * stereoSide * 0.5;
+        
+        // Sesin geldiği yan biraz daha aydınlık
+        finalColor *= 1.0 + stereoBalance * screenSide * 0.2;
+        
         // Kenar efektleri
         float edge = pow(1.0 - abs(dot(Normal, vec3(0.0, 0.0, 1.0))), 2.0);
         finalColor += rainbow(edge + timeShift) * edge * (bassEnergy + 0.2);
         
         // Glitch efekti
-        float glitchIntensity = step(0.98, sin(time * 50.0)) * highEnergy;
+        float glitchRate = bpm > 0.0 ? bpm / 60.0 * 6.28318 * 4.0 : 50.0;
+        float glitchIntensity = step(0.98, sin(time * glitchRate)) * highEnergy;
         vec3 glitchColor = rainbow(noise(uv * 100.0 + time));
         finalColor = mix(finalColor, glitchColor, glitchIntensity * 0.5);
         
+        // Vuruş flaşı
+        finalColor += vec3(flash * 0.6);
+        
         // Renk doygunluğu artırma
         finalColor = pow(finalColor, vec3(0.8)); // Renkleri daha canlı yap
         finalColor *= 1.2; // Parlaklığı artır
@@ -259,3 +362,102 @@ pub const FRAGMENT_SHADER: &str = r#"
         FragColor = vec4(finalColor, alpha);
     }
 "#;
+
+// Vektörskop ışını: her nokta bir sol/sağ çerçevesidir, parlaklığı ışının o
+// noktadaki hızıyla ters orantılıdır
+pub const SCOPE_VERTEX_SHADER: &str = r#"
+    #version 330 core
+    layout (location = 0) in vec2 aPos;
+    layout (location = 1) in float aIntensity;
+    
+    uniform float aspect;
+    
+    out float Intensity;
+    
+    void main() {
+        // Birim daire ekranın kısa kenarına sığar
+        vec2 fit = aspect > 1.0 ? vec2(1.0 / aspect, 1.0) : vec2(1.0, aspect);
+        Intensity = aIntensity;
+        gl_Position = vec4(aPos * fit * 0.9, 0.0, 1.0);
+    }
+"#;
+
+pub const SCOPE_FRAGMENT_SHADER: &str = r#"
+    #version 330 core
+    out vec4 FragColor;
+    
+    in float Intensity;
+    
+    uniform vec4 color;
+    
+    void main() {
+        FragColor = vec4(color.rgb * Intensity, 1.0);
+    }
+"#;
+
+// Fosfor dokusunu söndürmek ve ekrana çizmek için tam ekran dörtgen
+pub const PHOSPHOR_VERTEX_SHADER: &str = r#"
+    #version 330 core
+    layout (location = 0) in vec2 aPos;
+    
+    out vec2 ScreenPos;
+    
+    void main() {
+        ScreenPos = aPos;
+        gl_Position = vec4(aPos, 0.0, 1.0);
+    }
+"#;
+
+// Karışım SRC_ALPHA, ONE_MINUS_SRC_ALPHA iken dokudaki her değeri `1 - fade` ile çarpar
+pub const PHOSPHOR_FADE_SHADER: &str = r#"
+    #version 330 core
+    out vec4 FragColor;
+    
+    uniform float fade;
+    
+    void main() {
+        FragColor = vec4(0.0, 0.0, 0.0, fade);
+    }
+"#;
+
+pub const PHOSPHOR_PRESENT_SHADER: &str = r#"
+    #version 330 core
+    out vec4 FragColor;
+    
+    in vec2 ScreenPos;
+    
+    uniform sampler2D phosphor;
+    uniform float aspect;
+    uniform vec4 color;
+    
+    // Ekran uzayında yaklaşık bir piksel kalınlığında yumuşak çizgi
+    float line(float d) {
+        return 1.0 - smoothstep(0.0, fwidth(d) * 1.5, abs(d));
+    }
+    
+    void main() {
+        vec2 uv = ScreenPos * 0.5 + 0.5;
+        vec3 beam = texture(phosphor, uv).rgb;
+        
+        // Işının etrafındaki hafif hale
+        vec2 texel = 1.0 / vec2(textureSize(phosphor, 0));
+        vec3 halo = vec3(0.0);
+        for (int i = -2; i <= 2; i++) {
+            for (int j = -2; j <= 2; j++) {
+                halo += texture(phosphor, uv + vec2(i, j) * texel * 2.0).rgb;
+            }
+        }
+        beam += halo / 25.0 * 0.6;
+        
+        // Izgara: orta (dikey), yan (yatay), sol ve sağ köşegenler ve tam ölçek dairesi
+        vec2 fit = aspect > 1.0 ? vec2(1.0 / aspect, 1.0) : vec2(1.0, aspect);
+        vec2 p = ScreenPos / (fit * 0.9);
+        float grid = max(line(p.x), line(p.y));
+        grid = max(grid, max(line(p.x - p.y), line(p.x + p.y)) * step(length(p), 1.0));
+        grid = max(grid, line(length(p) - 1.0));
+        
+        vec3 glow = vec3(1.0) - exp(-beam * 1.5);
+        vec3 finalColor = glow + color.rgb * grid * 0.12;
+        FragColor = vec4(finalColor, 1.0);
+    }
+"#;
@@ -0,0 +1,286 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rodio::source::SeekError;
+use rodio::Source;
+use std::f64::consts::TAU;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+pub const SIGNAL_RATE: u32 = 44100;
+const SIGNAL_CHANNELS: u16 = 2;
+// -6 dBFS; akor tonları bunu paylaşır
+const AMPLITUDE: f32 = 0.5;
+// Gürültü her çalıştırmada aynı olsun ki dışa aktarılan değerler karşılaştırılabilsin
+const NOISE_SEED: u64 = 0;
+// Tek örneklik bir darbe pencerede -60 dB tabanının altında kalır; her vuruş
+// bunun yerine bu kadar süren, hızla sönen bir gürültü tıkıdır
+const CLICK_SECONDS: f64 = 0.01;
+const CLICK_DECAY_SECONDS: f64 = 0.002;
+
+// Analizi ayarlamak için sentetik sinyaller
+#[derive(Clone, Debug, PartialEq)]
+pub enum Signal {
+    Sine(f32),
+    // Logaritmik tarama; süre sonunda baştan başlar
+    Sweep { low: f32, high: f32, seconds: f32 },
+    WhiteNoise,
+    PinkNoise,
+    // Dakikada `bpm` kez kısa tık; tık vuruşun tam örneğinde başlar
+    Impulses(f32),
+    Chord(Vec<f32>),
+}
+
+impl fmt::Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Signal::Sine(hz) => write!(f, "sine {} Hz", hz),
+            Signal::Sweep { low, high, seconds } => {
+                write!(f, "log sweep {}-{} Hz over {} s", low, high, seconds)
+            }
+            Signal::WhiteNoise => write!(f, "white noise"),
+            Signal::PinkNoise => write!(f, "pink noise"),
+            Signal::Impulses(bpm) => write!(f, "impulses at {} BPM", bpm),
+            Signal::Chord(tones) => {
+                let tones: Vec<String> = tones.iter().map(|hz| hz.to_string()).collect();
+                write!(f, "chord {} Hz", tones.join("+"))
+            }
+        }
+    }
+}
+
+impl FromStr for Signal {
+    type Err = String;
+
+    // "tür[:parametreler]"; parametresiz türler varsayılanlarını kullanır
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, params) = match s.split_once(':') {
+            Some((kind, params)) => (kind, Some(params)),
+            None => (s, None),
+        };
+        let invalid = |expected: &str| format!("invalid signal '{}', expected {}", s, expected);
+
+        match (kind.to_ascii_lowercase().as_str(), params) {
+            ("sine", None) => Ok(Signal::Sine(440.0)),
+            ("sine", Some(hz)) => Ok(Signal::Sine(
+                parse_frequency(hz).ok_or_else(|| invalid("sine:HZ"))?,
+            )),
+            ("sweep", None) => Ok(Signal::Sweep {
+                low: 20.0,
+                high: 20000.0,
+                seconds: 10.0,
+            }),
+            ("sweep", Some(params)) => {
+                let invalid = || invalid("sweep:LOW-HIGH[:SECONDS]");
+                let (range, seconds) = match params.split_once(':') {
+                    Some((range, seconds)) => (range, Some(seconds)),
+                    None => (params, None),
+                };
+                let (low, high) = range.split_once('-').ok_or_else(invalid)?;
+                let low = parse_frequency(low).ok_or_else(invalid)?;
+                let high = parse_frequency(high).ok_or_else(invalid)?;
+                let seconds = match seconds {
+                    Some(seconds) => seconds
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|&seconds| seconds > 0.0 && seconds.is_finite())
+                        .ok_or_else(invalid)?,
+                    None => 10.0,
+                };
+                if low == high {
+                    return Err(invalid());
+                }
+                Ok(Signal::Sweep { low, high, seconds })
+            }
+            ("white", None) => Ok(Signal::WhiteNoise),
+            ("pink", None) => Ok(Signal::PinkNoise),
+            ("impulse", None) => Ok(Signal::Impulses(120.0)),
+            ("impulse", Some(bpm)) => {
+                let bpm = bpm
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|bpm| (1.0..=1000.0).contains(bpm))
+                    .ok_or_else(|| invalid("impulse:BPM with BPM in 1..=1000"))?;
+                Ok(Signal::Impulses(bpm))
+            }
+            // La majör
+            ("chord", None) => Ok(Signal::Chord(vec![220.0, 277.18, 329.63])),
+            ("chord", Some(tones)) => {
+                let tones: Option<Vec<f32>> = tones.split(',').map(parse_frequency).collect();
+                Ok(Signal::Chord(
+                    tones.ok_or_else(|| invalid("chord:HZ,HZ,..."))?,
+                ))
+            }
+            _ => Err(format!(
+                "unknown signal '{}', expected sine[:HZ], sweep[:LOW-HIGH[:SECONDS]], \
+                 white, pink, impulse[:BPM] or chord[:HZ,HZ,...]",
+                s
+            )),
+        }
+    }
+}
+
+// Nyquist'in altındaki pozitif frekanslar
+fn parse_frequency(value: &str) -> Option<f32> {
+    value
+        .trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|&hz| hz > 0.0 && hz < SIGNAL_RATE as f32 / 2.0)
+}
+
+// Sinyal örnek indeksinin saf işlevidir (gürültü hariç), bu yüzden atlama
+// yalnızca indeksi taşır. Aynı değer tüm kanallara yazılır.
+pub struct SignalSource {
+    signal: Signal,
+    frame: u64,
+    // None sonsuzdur
+    total_frames: Option<u64>,
+    // Çerçeve içindeki sıradaki kanal
+    channel: u16,
+    value: f32,
+    rng: StdRng,
+    // Pembe gürültü süzgecinin durumu
+    pink: [f32; 7],
+}
+
+impl SignalSource {
+    pub fn new(signal: Signal, duration: Option<f64>) -> Self {
+        Self {
+            signal,
+            frame: 0,
+            total_frames: duration.map(|seconds| (seconds * SIGNAL_RATE as f64) as u64),
+            channel: 0,
+            value: 0.0,
+            rng: StdRng::seed_from_u64(NOISE_SEED),
+            pink: [0.0; 7],
+        }
+    }
+
+    fn sample(&mut self) -> f32 {
+        let rate = SIGNAL_RATE as f64;
+        let t = self.frame as f64 / rate;
+        match &self.signal {
+            Signal::Sine(hz) => AMPLITUDE * (TAU * *hz as f64 * t).sin() as f32,
+            Signal::Sweep { low, high, seconds } => {
+                let (low, seconds) = (*low as f64, *seconds as f64);
+                let k = (*high as f64 / low).ln();
+                let t = t % seconds;
+                // Anlık frekans low * (high/low)^(t/T) olan fazın integrali
+                let phase = TAU * low * seconds / k * ((k * t / seconds).exp() - 1.0);
+                AMPLITUDE * phase.sin() as f32
+            }
+            Signal::WhiteNoise => AMPLITUDE * self.rng.gen_range(-1.0..1.0),
+            Signal::PinkNoise => {
+                // Paul Kellet'in süzgeci; beyaz gürültüden -3 dB/oktav
+                let white: f32 = self.rng.gen_range(-1.0..1.0);
+                let b = &mut self.pink;
+                b[0] = 0.99886 * b[0] + white * 0.0555179;
+                b[1] = 0.99332 * b[1] + white * 0.0750759;
+                b[2] = 0.96900 * b[2] + white * 0.153852;
+                b[3] = 0.86650 * b[3] + white * 0.3104856;
+                b[4] = 0.55000 * b[4] + white * 0.5329522;
+                b[5] = -0.7616 * b[5] - white * 0.0168980;
+                let pink = b[0] + b[1] + b[2] + b[3] + b[4] + b[5] + b[6] + white * 0.5362;
+                b[6] = white * 0.115926;
+                AMPLITUDE * (pink * 0.11).clamp(-1.0, 1.0)
+            }
+            Signal::Impulses(bpm) => {
+                let period = rate * 60.0 / *bpm as f64;
+                let offset = (self.frame as f64 % period) / rate;
+                if offset < CLICK_SECONDS {
+                    let noise: f32 = self.rng.gen_range(-1.0..1.0);
+                    AMPLITUDE * (-offset / CLICK_DECAY_SECONDS).exp() as f32 * noise
+                } else {
+                    0.0
+                }
+            }
+            Signal::Chord(tones) => {
+                let sum: f64 = tones.iter().map(|hz| (TAU * *hz as f64 * t).sin()).sum();
+                AMPLITUDE * (sum / tones.len() as f64) as f32
+            }
+        }
+    }
+}
+
+impl Iterator for SignalSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.channel == 0 {
+            if self.total_frames.is_some_and(|total| self.frame >= total) {
+                return None;
+            }
+            self.value = self.sample();
+        }
+        self.channel += 1;
+        if self.channel == SIGNAL_CHANNELS {
+            self.channel = 0;
+            self.frame += 1;
+        }
+        Some(self.value)
+    }
+}
+
+impl Source for SignalSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        SIGNAL_CHANNELS
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SIGNAL_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.total_frames
+            .map(|frames| Duration::from_secs_f64(frames as f64 / SIGNAL_RATE as f64))
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.frame = (pos.as_secs_f64() * SIGNAL_RATE as f64) as u64;
+        self.channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_signal_specs() {
+        assert_eq!("sine".parse(), Ok(Signal::Sine(440.0)));
+        assert_eq!(
+            "sweep:100-1000:5".parse(),
+            Ok(Signal::Sweep {
+                low: 100.0,
+                high: 1000.0,
+                seconds: 5.0
+            })
+        );
+        assert_eq!(
+            "chord:100,200".parse(),
+            Ok(Signal::Chord(vec![100.0, 200.0]))
+        );
+        assert!("sine:30000".parse::<Signal>().is_err());
+        assert!("chord:".parse::<Signal>().is_err());
+        assert!("white:1".parse::<Signal>().is_err());
+    }
+
+    #[test]
+    fn clicks_start_on_the_beat() {
+        let source = SignalSource::new(Signal::Impulses(120.0), Some(1.0));
+        let channels = SIGNAL_CHANNELS as usize;
+        let first: Vec<usize> = source
+            .enumerate()
+            .filter(|(index, sample)| index % channels == 0 && *sample != 0.0)
+            .map(|(index, _)| index / channels)
+            .filter(|frame| frame % (SIGNAL_RATE as usize / 2) == 0)
+            .collect();
+        assert_eq!(first, vec![0, SIGNAL_RATE as usize / 2]);
+    }
+}
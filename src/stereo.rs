@@ -0,0 +1,174 @@
+use crate::analysis::{AnalyzerConfig, SpectrumAnalyzer, MAX_DB, MIN_DB};
+use crate::bands::Band;
+
+// Altındaki güç sessizlik sayılır; denge ve genişlik 0 kalır
+const SILENCE_POWER: f32 = 1e-10;
+
+// Sol/sağ kanalların ayrı ayrı ve birlikte görünümü; mono kaynaklarda
+// iki kanal aynıdır, genişlik 0, denge ve pan 0 okunur
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StereoFeatures {
+    // `AudioFeatures::spectrum` ile aynı ölçekte
+    pub left_spectrum: Vec<f32>,
+    pub right_spectrum: Vec<f32>,
+    // Orta (L+R)/2 ve yan (L-R)/2 sinyallerin seviyesi, 0..1 normalize dB;
+    // spektrum gibi tam ölçekli sinüs 0 dB okunur
+    pub mid: f32,
+    pub side: f32,
+    // Yan gücün toplam içindeki payı: 0 mono, 0.5 ilintisiz kanallar, 1 ters faz
+    pub width: f32,
+    // Güç dengesi, -1 tamamen sol, 1 tamamen sağ
+    pub balance: f32,
+    // `AnalyzerConfig::bands` ile aynı sırada, -1..1
+    pub band_pan: Vec<f32>,
+}
+
+impl StereoFeatures {
+    pub fn silence(&mut self) {
+        self.left_spectrum.iter_mut().for_each(|value| *value = 0.0);
+        self.right_spectrum
+            .iter_mut()
+            .for_each(|value| *value = 0.0);
+        self.mid = 0.0;
+        self.side = 0.0;
+        self.width = 0.0;
+        self.balance = 0.0;
+        self.band_pan.iter_mut().for_each(|value| *value = 0.0);
+    }
+}
+
+// Her kanal için ayrı FFT; orta/yan ölçüleri zaman alanında hesaplanır
+pub struct StereoAnalyzer {
+    left: SpectrumAnalyzer,
+    right: SpectrumAnalyzer,
+    bands: Vec<Band>,
+}
+
+impl StereoAnalyzer {
+    pub fn new(config: &AnalyzerConfig, sample_rate: u32) -> Self {
+        Self {
+            left: SpectrumAnalyzer::new(config, sample_rate),
+            right: SpectrumAnalyzer::new(config, sample_rate),
+            bands: config.bands.clone(),
+        }
+    }
+
+    // `left` ve `right` aynı pencerenin iki kanalıdır, `fft_size` uzunluğunda
+    pub fn process(&mut self, left: &[f32], right: &[f32], out: &mut StereoFeatures) {
+        self.left.transform(left);
+        self.left.bin(&mut out.left_spectrum);
+        self.right.transform(right);
+        self.right.bin(&mut out.right_spectrum);
+
+        let (mut left_power, mut right_power) = (0.0, 0.0);
+        let (mut mid_power, mut side_power) = (0.0, 0.0);
+        for (&l, &r) in left.iter().zip(right) {
+            left_power += l * l;
+            right_power += r * r;
+            let (mid, side) = ((l + r) * 0.5, (l - r) * 0.5);
+            mid_power += mid * mid;
+            side_power += side * side;
+        }
+        let len = left.len().max(1) as f32;
+        out.mid = power_level(mid_power / len);
+        out.side = power_level(side_power / len);
+        out.width = ratio(side_power, mid_power + side_power);
+        out.balance = pan(left_power, right_power);
+
+        // Bant pan'ı doğrusal güçten; tabandaki bin'ler iki yana eşit katkı verir
+        let bin_hz = self.left.bin_hz();
+        let (left_levels, right_levels) = (self.left.spectrum(), self.right.spectrum());
+        out.band_pan.clear();
+        for band in &self.bands {
+            let bins = band.bins(bin_hz, left_levels.len());
+            let left_power: f32 = left_levels[bins.clone()]
+                .iter()
+                .map(|&l| level_power(l))
+                .sum();
+            let right_power: f32 = right_levels[bins].iter().map(|&l| level_power(l)).sum();
+            out.band_pan.push(pan(left_power, right_power));
+        }
+    }
+}
+
+// Ortalama güç -> 0..1 normalize dB; tepe değeri √2 kat olan sinüs 0 dB'dir
+fn power_level(power: f32) -> f32 {
+    if power <= SILENCE_POWER {
+        return 0.0;
+    }
+    let db = 10.0 * (power * 2.0).log10();
+    ((db - MIN_DB) / (MAX_DB - MIN_DB)).clamp(0.0, 1.0)
+}
+
+fn level_power(level: f32) -> f32 {
+    10f32.powf((MIN_DB + level * (MAX_DB - MIN_DB)) / 10.0)
+}
+
+fn ratio(part: f32, total: f32) -> f32 {
+    if total <= SILENCE_POWER {
+        0.0
+    } else {
+        (part / total).clamp(0.0, 1.0)
+    }
+}
+
+fn pan(left_power: f32, right_power: f32) -> f32 {
+    let total = left_power + right_power;
+    if total <= SILENCE_POWER {
+        0.0
+    } else {
+        ((right_power - left_power) / total).clamp(-1.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn sine(frequency: f32, amplitude: f32) -> Vec<f32> {
+        (0..2048)
+            .map(|i| amplitude * (2.0 * PI * frequency * i as f32 / 44100.0).sin())
+            .collect()
+    }
+
+    fn analyze(left: &[f32], right: &[f32]) -> StereoFeatures {
+        let config = AnalyzerConfig {
+            bands: vec![
+                Band::new("low", 20.0, 200.0),
+                Band::new("high", 2000.0, 20000.0),
+            ],
+            ..AnalyzerConfig::default()
+        };
+        let mut features = StereoFeatures::default();
+        StereoAnalyzer::new(&config, 44100).process(left, right, &mut features);
+        features
+    }
+
+    #[test]
+    fn mono_is_centered_and_narrow() {
+        let tone = sine(100.0, 0.5);
+        let features = analyze(&tone, &tone);
+        assert_eq!(features.width, 0.0);
+        assert_eq!(features.balance, 0.0);
+        assert_eq!(features.side, 0.0);
+        assert!(features.band_pan.iter().all(|&pan| pan.abs() < 1e-3));
+        assert_eq!(features.left_spectrum, features.right_spectrum);
+    }
+
+    #[test]
+    fn pans_follow_the_louder_channel() {
+        let features = analyze(&sine(100.0, 0.5), &sine(5000.0, 0.5));
+        assert!(features.band_pan[0] < -0.99, "{:?}", features.band_pan);
+        assert!(features.band_pan[1] > 0.99, "{:?}", features.band_pan);
+        assert!(features.balance.abs() < 0.05, "{}", features.balance);
+
+        let silent = vec![0.0; 2048];
+        let features = analyze(&sine(1000.0, 0.5), &silent);
+        assert!((features.balance + 1.0).abs() < 1e-6);
+        assert!((features.width - 0.5).abs() < 1e-3);
+
+        let inverted: Vec<f32> = sine(300.0, 0.5).iter().map(|&x| -x).collect();
+        assert!((analyze(&sine(300.0, 0.5), &inverted).width - 1.0).abs() < 1e-6);
+    }
+}
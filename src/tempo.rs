@@ -0,0 +1,238 @@
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+
+// Otokorelasyon için tutulan başlangıç zarfı uzunluğu
+const ENVELOPE_SECONDS: f64 = 8.0;
+// Tahminin ne sıklıkla yenilendiği
+const ESTIMATE_INTERVAL: f64 = 0.5;
+const MIN_BPM: f64 = 70.0;
+const MAX_BPM: f64 = 180.0;
+// Oktav hatalarını azaltmak için tempo önseli (log-normal, 120 BPM merkezli)
+const PRIOR_BPM: f64 = 120.0;
+const PRIOR_WIDTH: f64 = 1.0;
+const BEATS_PER_BAR: u32 = 4;
+// Atlamadan sonra faz, bilinen tempoyla bu kadar vuruşluk zarf birikince yeniden hizalanır
+const REALIGN_BEATS: f64 = 4.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TempoEstimate {
+    pub bpm: f32,
+    // 0..1; zayıf ya da ritimsiz müzikte sıfıra yakın
+    pub confidence: f32,
+    // Vuruşun içindeki konum, vuruş anında 0
+    pub beat_phase: f32,
+    // Ölçünün içindeki konum, ilk vuruşta 0
+    pub bar_phase: f32,
+}
+
+// Başlangıç zarfının otokorelasyonundan tempo, tarak filtresinden vuruş ve ölçü fazı
+pub struct TempoTracker {
+    envelope: VecDeque<f32>,
+    capacity: usize,
+    hop_seconds: f64,
+    since_estimate: f64,
+    // Saniye cinsinden vuruş periyodu; 0 ise henüz tahmin yok
+    period: f64,
+    confidence: f32,
+    // Bilinen bir vuruşun zamanı; None ise faz henüz hizalanmadı
+    beat_origin: Option<f64>,
+    // `beat_origin`'den sayıldığında ölçü başına denk gelen vuruş (mod 4)
+    bar_beat: u32,
+}
+
+impl TempoTracker {
+    pub fn new(hop_seconds: f64) -> Self {
+        let capacity = (ENVELOPE_SECONDS / hop_seconds).round() as usize;
+        Self {
+            envelope: VecDeque::with_capacity(capacity),
+            capacity,
+            hop_seconds,
+            since_estimate: 0.0,
+            period: 0.0,
+            confidence: 0.0,
+            beat_origin: None,
+            bar_beat: 0,
+        }
+    }
+
+    // Zarf atlamadan önceki ve sonraki başlangıçları birleştirmesin. Tempo parça
+    // boyunca korunur; faz, zarfın tamamını beklemeden yeniden hizalanır.
+    pub fn seek(&mut self) {
+        self.envelope.clear();
+        self.since_estimate = 0.0;
+        self.beat_origin = None;
+        self.bar_beat = 0;
+    }
+
+    // `onset` başlangıç zarfının bu kareye ait değeri, `time` kare zamanıdır
+    pub fn process(&mut self, onset: f32, time: f64) -> TempoEstimate {
+        self.envelope.push_back(onset);
+        if self.envelope.len() > self.capacity {
+            self.envelope.pop_front();
+        }
+
+        self.since_estimate += self.hop_seconds;
+        if self.since_estimate >= ESTIMATE_INTERVAL {
+            let covered = self.envelope.len() as f64 * self.hop_seconds;
+            if self.envelope.len() >= self.capacity / 2 {
+                self.since_estimate = 0.0;
+                self.estimate(time);
+            } else if self.beat_origin.is_none()
+                && self.period > 0.0
+                && covered >= REALIGN_BEATS * self.period
+            {
+                self.since_estimate = 0.0;
+                self.align_phase(time);
+            }
+        }
+
+        let Some(beat_origin) = self.beat_origin else {
+            return TempoEstimate::default();
+        };
+
+        let beats = (time - beat_origin) / self.period;
+        let bars = (beats - self.bar_beat as f64) / BEATS_PER_BAR as f64;
+        TempoEstimate {
+            bpm: (60.0 / self.period) as f32,
+            confidence: self.confidence,
+            beat_phase: beats.rem_euclid(1.0) as f32,
+            bar_phase: bars.rem_euclid(1.0) as f32,
+        }
+    }
+
+    fn estimate(&mut self, time: f64) {
+        // Hop ızgarasına tam oturmayan periyotlar için zarf hafifçe yumuşatılır
+        let smoothed: Vec<f32> = (0..self.envelope.len())
+            .map(|i| {
+                let previous = self.envelope[i.saturating_sub(1)];
+                let next = *self.envelope.get(i + 1).unwrap_or(&self.envelope[i]);
+                0.25 * previous + 0.5 * self.envelope[i] + 0.25 * next
+            })
+            .collect();
+        let mean = smoothed.iter().sum::<f32>() / smoothed.len() as f32;
+        let signal: Vec<f32> = smoothed.iter().map(|value| value - mean).collect();
+        let energy: f32 = signal.iter().map(|value| value * value).sum();
+        if energy <= f32::EPSILON {
+            self.confidence = 0.0;
+            return;
+        }
+
+        let min_lag = (60.0 / MAX_BPM / self.hop_seconds).floor().max(1.0) as usize;
+        let max_lag = ((60.0 / MIN_BPM / self.hop_seconds).ceil() as usize).min(signal.len() / 2);
+        if min_lag + 2 > max_lag {
+            return;
+        }
+
+        let autocorrelation: Vec<f32> = (0..=(max_lag + 1) * 2)
+            .map(|lag| {
+                signal
+                    .get(lag..)
+                    .unwrap_or_default()
+                    .iter()
+                    .zip(&signal)
+                    .map(|(a, b)| a * b)
+                    .sum::<f32>()
+                    / energy
+            })
+            .collect();
+
+        let weight = |lag: f64| {
+            let bpm = 60.0 / (lag * self.hop_seconds);
+            let octaves = (bpm / PRIOR_BPM).log2() / PRIOR_WIDTH;
+            (-0.5 * octaves * octaves).exp() as f32
+        };
+        // İkinci harmoniği de katmak, yarım tempoya kaymayı önler
+        let score = |lag: usize| {
+            let harmonic = autocorrelation.get(lag * 2).copied().unwrap_or(0.0);
+            (autocorrelation[lag] + 0.5 * harmonic) * weight(lag as f64)
+        };
+        let Some(best) = (min_lag..=max_lag).max_by(|&a, &b| score(a).total_cmp(&score(b))) else {
+            return;
+        };
+
+        // Parabolik ara değerle bin altı gecikme
+        let (left, center, right) = (
+            autocorrelation[best - 1],
+            autocorrelation[best],
+            autocorrelation[best + 1],
+        );
+        let curvature = left - 2.0 * center + right;
+        let offset = if curvature.abs() > f32::EPSILON {
+            (0.5 * (left - right) / curvature).clamp(-0.5, 0.5)
+        } else {
+            0.0
+        };
+        let period = (best as f64 + offset as f64) * self.hop_seconds;
+        let confidence = center.clamp(0.0, 1.0);
+
+        // Yakın tahminleri yumuşat, uzak olanlara yalnızca daha güvenliyse geç
+        if self.period > 0.0 && (period / self.period - 1.0).abs() < 0.05 {
+            self.period = self.period * 0.7 + period * 0.3;
+        } else if self.period <= 0.0 || confidence > self.confidence {
+            self.period = period;
+        }
+        self.confidence = self.confidence * 0.5 + confidence * 0.5;
+
+        self.align_phase(time);
+    }
+
+    // Zarf üzerinde periyot aralıklı tarak ile en güçlü vuruş ve ölçü başı hizası.
+    // Vuruş fazı yumuşakça düzeltilir; ölçü başı yalnızca belirgin şekilde daha güçlü
+    // bir aday varsa değişir, böylece eşit vuruşlu müzikte faz sıçramaz.
+    fn align_phase(&mut self, time: f64) {
+        let period = self.period / self.hop_seconds;
+        let last = self.envelope.len() as f64 - 1.0;
+        let comb = |offset: f64, step: f64| {
+            let mut sum = 0.0;
+            let mut position = last - offset;
+            while position >= 0.0 {
+                sum += self.envelope[position.round() as usize];
+                position -= step;
+            }
+            sum
+        };
+
+        let beat_offset = (0..period.ceil() as usize)
+            .map(|offset| offset as f64)
+            .max_by(|&a, &b| comb(a, period).total_cmp(&comb(b, period)))
+            .unwrap_or(0.0);
+
+        let beat_time = time - beat_offset * self.hop_seconds;
+        let origin = match self.beat_origin {
+            None => beat_time,
+            Some(origin) => {
+                let error = (beat_time - origin) / self.period;
+                origin + (error - error.round()) * self.period * 0.5
+            }
+        };
+        self.beat_origin = Some(origin);
+
+        // Ölçü başı adayları: son vuruştan geriye 0..3 vuruş
+        let mut strengths = [0.0f32; BEATS_PER_BAR as usize];
+        for beat in 0..BEATS_PER_BAR {
+            let offset = beat_offset + beat as f64 * period;
+            let candidate = time - offset * self.hop_seconds;
+            let index = ((candidate - origin) / self.period).round() as i64;
+            let residue = index.rem_euclid(BEATS_PER_BAR as i64) as usize;
+            strengths[residue] = comb(offset, period * BEATS_PER_BAR as f64);
+        }
+        let (best, &strength) = strengths
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap_or((0, &0.0));
+        if strength > strengths[self.bar_beat as usize] * 1.1 {
+            self.bar_beat = best as u32;
+        }
+    }
+}
+
+// Görsellerde nabız için: vuruş anında 1, vuruş sonuna doğru 0
+pub fn beat_pulse(beat_phase: f32) -> f32 {
+    (1.0 - beat_phase).powi(2)
+}
+
+// Ölçüye kilitli salınım, -1..1
+pub fn bar_wave(bar_phase: f32) -> f32 {
+    (bar_phase * 2.0 * PI).sin()
+}
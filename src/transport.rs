@@ -0,0 +1,280 @@
+use rodio::Source;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::playback::PlaybackClock;
+
+// Atomik çerçeve alanlarında "yok" değeri
+const NO_FRAME: u64 = u64::MAX;
+
+// Pencere iş parçacığının verdiği çalma komutları; ses iş parçacığı bunları
+// `TransportSource` içinde çerçeve sınırlarında uygular
+pub struct Transport {
+    clock: Arc<PlaybackClock>,
+    sample_rate: u32,
+    paused: AtomicBool,
+    // f32 bitleri
+    volume: AtomicU32,
+    seek_to: AtomicU64,
+    loop_start: AtomicU64,
+    loop_end: AtomicU64,
+}
+
+impl Transport {
+    pub fn new(clock: Arc<PlaybackClock>, sample_rate: u32, volume: f32) -> Self {
+        Self {
+            clock,
+            sample_rate: sample_rate.max(1),
+            paused: AtomicBool::new(false),
+            volume: AtomicU32::new(volume.to_bits()),
+            seek_to: AtomicU64::new(NO_FRAME),
+            loop_start: AtomicU64::new(NO_FRAME),
+            loop_end: AtomicU64::new(NO_FRAME),
+        }
+    }
+
+    // Duyulan konum, saniye
+    pub fn position(&self) -> f64 {
+        self.clock.frames() as f64 / self.sample_rate as f64
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    pub fn set_volume(&self, volume: f32) {
+        self.volume.store(volume.to_bits(), Ordering::Relaxed);
+    }
+
+    // Parça sonundan ötesi parçayı bitirir
+    pub fn seek(&self, seconds: f64) {
+        self.seek_to
+            .store(self.to_frame(seconds), Ordering::Relaxed);
+    }
+
+    // Konum `end`'e gelince `start`'a dönülür; None döngüyü kaldırır
+    pub fn set_loop(&self, region: Option<(f64, f64)>) {
+        let (start, end) = match region {
+            Some((start, end)) => (self.to_frame(start), self.to_frame(end)),
+            None => (NO_FRAME, NO_FRAME),
+        };
+        // Bitiş önce kaldırılır ki ses iş parçacığı yarım bir bölge görmesin
+        self.loop_end.store(NO_FRAME, Ordering::Release);
+        self.loop_start.store(start, Ordering::Release);
+        self.loop_end.store(end, Ordering::Release);
+    }
+
+    fn to_frame(&self, seconds: f64) -> u64 {
+        (seconds.max(0.0) * self.sample_rate as f64) as u64
+    }
+
+    fn frame_duration(&self, frame: u64) -> Duration {
+        Duration::from_secs_f64(frame as f64 / self.sample_rate as f64)
+    }
+}
+
+// Duraklatma, ses düzeyi, atlama ve A/B döngüsü. Komutlar yalnızca çerçeve
+// sınırlarında uygulanır, böylece kanallar kaymaz.
+pub struct TransportSource<S> {
+    inner: S,
+    transport: Arc<Transport>,
+    channels: usize,
+    // Çerçeve içindeki sıradaki kanal
+    fill: usize,
+    // Bu çerçeve sessiz mi (duraklatılmış)
+    silent: bool,
+}
+
+impl<S> TransportSource<S>
+where
+    S: Source<Item = f32>,
+{
+    pub fn new(inner: S, transport: Arc<Transport>) -> Self {
+        Self {
+            channels: inner.channels().max(1) as usize,
+            inner,
+            transport,
+            fill: 0,
+            silent: false,
+        }
+    }
+
+    // Bekleyen atlama ya da döngü sonu; parçanın ötesine atlanırsa false
+    fn apply_seek(&mut self) -> bool {
+        let transport = &self.transport;
+        let mut target = transport.seek_to.swap(NO_FRAME, Ordering::Relaxed);
+        if target == NO_FRAME {
+            let end = transport.loop_end.load(Ordering::Acquire);
+            if end != NO_FRAME && transport.clock.frames() >= end {
+                target = transport.loop_start.load(Ordering::Acquire);
+            }
+        }
+        if target == NO_FRAME {
+            return true;
+        }
+
+        let position = transport.frame_duration(target);
+        if self
+            .inner
+            .total_duration()
+            .is_some_and(|duration| position >= duration)
+        {
+            return false;
+        }
+        if let Err(err) = self.inner.try_seek(position) {
+            eprintln!("warning: seek failed: {}", err);
+        }
+        true
+    }
+}
+
+impl<S> Iterator for TransportSource<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.fill == 0 {
+            if !self.apply_seek() {
+                self.transport.clock.finish();
+                return None;
+            }
+            self.silent = self.transport.is_paused();
+        }
+        self.fill = (self.fill + 1) % self.channels;
+
+        // Duraklatılmışken iç kaynaktan çekilmez; saat ve analiz de durur
+        if self.silent {
+            return Some(0.0);
+        }
+        let volume = f32::from_bits(self.transport.volume.load(Ordering::Relaxed));
+        self.inner.next().map(|sample| sample * volume)
+    }
+}
+
+impl<S> Source for TransportSource<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::playback::{SampleRing, TeeSource};
+    use rodio::buffer::SamplesBuffer;
+    use std::sync::Mutex;
+
+    const RATE: u32 = 1000;
+
+    struct Player {
+        source: TransportSource<TeeSource<SamplesBuffer<f32>>>,
+        transport: Arc<Transport>,
+        clock: Arc<PlaybackClock>,
+        ring: Arc<Mutex<SampleRing>>,
+    }
+
+    // Çerçeve indisi solda, eksisi sağda olan bir saniyelik stereo kaynak
+    fn player() -> Player {
+        let samples: Vec<f32> = (0..RATE).flat_map(|i| [i as f32, -(i as f32)]).collect();
+        let clock = Arc::new(PlaybackClock::new(2));
+        let ring = Arc::new(Mutex::new(SampleRing::new(4096)));
+        let tee = TeeSource::new(
+            SamplesBuffer::new(2, RATE, samples),
+            clock.clone(),
+            ring.clone(),
+        );
+        let transport = Arc::new(Transport::new(clock.clone(), RATE, 1.0));
+        Player {
+            source: TransportSource::new(tee, transport.clone()),
+            transport,
+            clock,
+            ring,
+        }
+    }
+
+    impl Player {
+        // Sonraki `frames` çerçevenin sol kanalı
+        fn play(&mut self, frames: usize) -> Vec<f32> {
+            let samples: Vec<f32> = self.source.by_ref().take(frames * 2).collect();
+            samples.iter().step_by(2).copied().collect()
+        }
+    }
+
+    #[test]
+    fn seek_moves_clock_and_ring() {
+        let mut player = player();
+        player.play(100);
+        player.transport.seek(0.5);
+        assert_eq!(
+            player.play(10),
+            (500..510).map(|i| i as f32).collect::<Vec<_>>()
+        );
+        assert_eq!(player.clock.frames(), 510);
+        assert_eq!(player.clock.seek_count(), 1);
+
+        // Halka atlanan konumdan yazılır, öncesi okunmaz; yazım toplu yapılır
+        player.play(300);
+        let (mut left, mut right) = ([0.0; 2], [0.0; 2]);
+        let ring = player.ring.lock().unwrap();
+        ring.read(99, &mut left, &mut right);
+        assert_eq!(left, [0.0; 2]);
+        ring.read(500, &mut left, &mut right);
+        assert_eq!((left, right), ([500.0, 501.0], [-500.0, -501.0]));
+    }
+
+    #[test]
+    fn loop_returns_to_a_at_b() {
+        let mut player = player();
+        player.transport.set_loop(Some((0.1, 0.2)));
+        let left = player.play(300);
+        assert_eq!(left[199], 199.0);
+        assert_eq!(left[200], 100.0);
+        assert_eq!(left[299], 199.0);
+
+        player.transport.set_loop(None);
+        assert_eq!(player.play(1), vec![200.0]);
+    }
+
+    #[test]
+    fn paused_source_does_not_pull() {
+        let mut player = player();
+        player.play(10);
+        player.transport.set_paused(true);
+        assert_eq!(player.play(50), vec![0.0; 50]);
+        assert_eq!(player.clock.frames(), 10);
+
+        player.transport.set_paused(false);
+        assert_eq!(player.play(1), vec![10.0]);
+    }
+
+    #[test]
+    fn seek_past_end_finishes_clock() {
+        let mut player = player();
+        player.play(10);
+        player.transport.seek(5.0);
+        assert_eq!(player.source.next(), None);
+        assert!(player.clock.is_finished());
+    }
+}
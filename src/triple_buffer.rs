@@ -0,0 +1,125 @@
+use std::cell::UnsafeCell;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+// `back` değerinde, yazarın okuyucunun henüz görmediği yeni bir kare bıraktığını gösteren bit
+const DIRTY: u8 = 0b100;
+const INDEX: u8 = 0b011;
+
+// Tek yazar, tek okuyucu; hiçbir taraf diğerini beklemez. Yazar kendi
+// yuvasını doldurup ortadaki yuvayla takas eder, okuyucu da yeni kare varsa
+// kendi yuvasını ortadakiyle takas eder. Okuyucu her zaman tek bir tutarlı kare görür.
+struct Shared<T> {
+    slots: [UnsafeCell<T>; 3],
+    back: AtomicU8,
+}
+
+// Her yuvaya aynı anda en fazla bir taraf erişir; sahiplik `back` takaslarıyla devredilir
+unsafe impl<T: Send> Sync for Shared<T> {}
+
+pub struct Publisher<T> {
+    shared: Arc<Shared<T>>,
+    index: u8,
+}
+
+pub struct Reader<T> {
+    shared: Arc<Shared<T>>,
+    index: u8,
+}
+
+pub fn triple_buffer<T: Clone>(initial: T) -> (Publisher<T>, Reader<T>) {
+    let shared = Arc::new(Shared {
+        slots: [
+            UnsafeCell::new(initial.clone()),
+            UnsafeCell::new(initial.clone()),
+            UnsafeCell::new(initial),
+        ],
+        back: AtomicU8::new(1),
+    });
+
+    (
+        Publisher {
+            shared: shared.clone(),
+            index: 0,
+        },
+        Reader { shared, index: 2 },
+    )
+}
+
+impl<T> Publisher<T> {
+    // Yazarın kendi yuvası; içinde iki kare önceki veri bulunabilir, tamamı yazılmalıdır
+    pub fn write(&mut self) -> &mut T {
+        unsafe { &mut *self.shared.slots[self.index as usize].get() }
+    }
+
+    pub fn publish(&mut self) {
+        let previous = self.shared.back.swap(self.index | DIRTY, Ordering::AcqRel);
+        self.index = previous & INDEX;
+    }
+}
+
+impl<T> Reader<T> {
+    // En son yayımlanan kare; yeni kare yoksa bir öncekiyle aynıdır
+    pub fn read(&mut self) -> &T {
+        if self.shared.back.load(Ordering::Relaxed) & DIRTY != 0 {
+            let previous = self.shared.back.swap(self.index, Ordering::AcqRel);
+            self.index = previous & INDEX;
+        }
+        unsafe { &*self.shared.slots[self.index as usize].get() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn read_sees_each_frame_once_published() {
+        let (mut publisher, mut reader) = triple_buffer(0u32);
+        assert_eq!(*reader.read(), 0);
+
+        *publisher.write() = 1;
+        // Yayımlanmayan kare okuyucuya görünmez
+        assert_eq!(*reader.read(), 0);
+        publisher.publish();
+        assert_eq!(*reader.read(), 1);
+
+        // Yeni kare yokken okuyucu yuvasını değiştirmez
+        let index = reader.index;
+        assert_eq!(*reader.read(), 1);
+        assert_eq!(reader.index, index);
+        assert_eq!(reader.shared.back.load(Ordering::Relaxed) & DIRTY, 0);
+
+        // Okunmadan üzerine yazılan kare atlanır, yalnızca en sonuncusu görülür
+        for value in 2..5 {
+            *publisher.write() = value;
+            publisher.publish();
+        }
+        assert_eq!(*reader.read(), 4);
+    }
+
+    #[test]
+    fn reader_never_sees_torn_or_older_frames() {
+        const FRAMES: u64 = 200_000;
+        let (mut publisher, mut reader) = triple_buffer([0u64; 32]);
+
+        let writer = thread::spawn(move || {
+            for counter in 1..=FRAMES {
+                // Karenin tamamı aynı sayaçla doldurulur; yarım kalan bir yazma karışık görünür
+                publisher.write().fill(counter);
+                publisher.publish();
+            }
+        });
+
+        let mut last = 0;
+        while last < FRAMES {
+            let frame = reader.read();
+            let counter = frame[0];
+            assert!(frame.iter().all(|&value| value == counter), "torn frame");
+            assert!(counter >= last, "went back from {} to {}", last, counter);
+            last = counter;
+        }
+        writer.join().unwrap();
+    }
+}
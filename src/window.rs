@@ -0,0 +1,79 @@
+use std::f32::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowFunction {
+    Rectangular,
+    Hann,
+    Hamming,
+    BlackmanHarris,
+    FlatTop,
+}
+
+impl WindowFunction {
+    pub const NAMES: &'static str = "rectangular, hann, hamming, blackman-harris, flat-top";
+
+    // Periyodik pencere katsayıları (spektral analiz için N paydalı biçim)
+    pub fn coefficients(self, size: usize) -> Vec<f32> {
+        let terms: &[f32] = match self {
+            WindowFunction::Rectangular => &[1.0],
+            WindowFunction::Hann => &[0.5, 0.5],
+            WindowFunction::Hamming => &[0.54, 0.46],
+            WindowFunction::BlackmanHarris => &[0.35875, 0.48829, 0.14128, 0.01168],
+            WindowFunction::FlatTop => &[
+                0.215_578_95,
+                0.416_631_58,
+                0.277_263_16,
+                0.083_578_95,
+                0.006_947_37,
+            ],
+        };
+
+        (0..size)
+            .map(|n| {
+                let x = 2.0 * PI * n as f32 / size as f32;
+                terms
+                    .iter()
+                    .enumerate()
+                    .map(|(k, a)| {
+                        let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+                        sign * a * (k as f32 * x).cos()
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+impl FromStr for WindowFunction {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name {
+            "rectangular" | "none" => Ok(WindowFunction::Rectangular),
+            "hann" => Ok(WindowFunction::Hann),
+            "hamming" => Ok(WindowFunction::Hamming),
+            "blackman-harris" => Ok(WindowFunction::BlackmanHarris),
+            "flat-top" => Ok(WindowFunction::FlatTop),
+            _ => Err(format!(
+                "unknown window '{}', expected one of: {}",
+                name,
+                WindowFunction::NAMES
+            )),
+        }
+    }
+}
+
+impl fmt::Display for WindowFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            WindowFunction::Rectangular => "rectangular",
+            WindowFunction::Hann => "hann",
+            WindowFunction::Hamming => "hamming",
+            WindowFunction::BlackmanHarris => "blackman-harris",
+            WindowFunction::FlatTop => "flat-top",
+        };
+        f.write_str(name)
+    }
+}